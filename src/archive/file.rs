@@ -0,0 +1,138 @@
+use std::io::prelude::*;
+use std::io;
+use std::str;
+use archive::types;
+use {Error, Object};
+
+/// Parse a space-padded decimal (or octal, for the mode) header field
+fn parse_field(field: &[u8], radix: u32) -> Result<u64, Error> {
+    let s = try!(str::from_utf8(field).map_err(|_| Error::from("invalid ar member header"))).trim();
+    if s.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(s, radix).map_err(|_| Error::from("invalid ar member header"))
+}
+
+/// Long member name from the GNU `//` table, which ends each name with "/\n"
+fn extended_name(names: &[u8], off: usize) -> Result<String, Error> {
+    if off >= names.len() {
+        return Err(Error::Truncated { offset: off as u64 });
+    }
+    let rest = &names[off..];
+    let end = rest.iter().position(|x| *x == b'\n').unwrap_or(rest.len());
+    let name = &rest[..end];
+    let name = if name.ends_with(b"/") { &name[..name.len() - 1] } else { name };
+    Ok(String::from_utf8_lossy(name).into_owned())
+}
+
+struct Member {
+    hdr: types::MemberHeader,
+    offset: usize,
+}
+
+/// A static library (`ar` archive)
+pub struct Archive {
+    data: Vec<u8>,
+    members: Vec<Member>,
+}
+
+impl Archive {
+    pub fn parse<R: io::Read + io::Seek>(r: &mut R) -> Result<Archive, Error> {
+        try!(r.seek(io::SeekFrom::Start(0)));
+        let mut data = Vec::new();
+        try!(r.read_to_end(&mut data));
+
+        if data.len() < types::ARMAG.len() || &data[..types::ARMAG.len()] != types::ARMAG {
+            try!(Err(Error::BadMagic));
+        }
+
+        let mut names: Option<(usize, usize)> = None;
+        let mut members = Vec::new();
+        let mut off = types::ARMAG.len();
+        while off < data.len() {
+            if off + types::AR_HDR_SIZE > data.len() {
+                return Err(Error::Truncated { offset: off as u64 });
+            }
+            let hdr = &data[off..off + types::AR_HDR_SIZE];
+            if &hdr[58..60] != types::ARFMAG {
+                try!(Err(Error::from("invalid ar member header")));
+            }
+            let raw_name = String::from_utf8_lossy(&hdr[0..16]).trim_end().to_string();
+            let date = try!(parse_field(&hdr[16..28], 10));
+            let uid = try!(parse_field(&hdr[28..34], 10)) as u32;
+            let gid = try!(parse_field(&hdr[34..40], 10)) as u32;
+            let mode = try!(parse_field(&hdr[40..48], 8)) as u32;
+            let mut size = try!(parse_field(&hdr[48..58], 10));
+
+            let mut start = off + types::AR_HDR_SIZE;
+            let end = match (start as u64).checked_add(size) {
+                Some(end) if end <= data.len() as u64 => end as usize,
+                _ => return Err(Error::Truncated { offset: off as u64 }),
+            };
+
+            let name = if raw_name == types::AR_SYMTAB || raw_name == types::AR_SYMTAB64 {
+                None
+            } else if raw_name == types::AR_NAMES {
+                names = Some((start, end));
+                None
+            } else if raw_name.starts_with(types::AR_BSD_NAME) {
+                // The name is stored at the start of the member data
+                let len = try!(parse_field(raw_name[types::AR_BSD_NAME.len()..].as_bytes(), 10)) as usize;
+                if len > end - start {
+                    return Err(Error::Truncated { offset: start as u64 });
+                }
+                let name = &data[start..start + len];
+                let len_nul = name.iter().position(|x| *x == 0).unwrap_or(len);
+                let name = String::from_utf8_lossy(&name[..len_nul]).into_owned();
+                start += len;
+                size -= len as u64;
+                Some(name)
+            } else if raw_name.starts_with('/') {
+                let name_off = try!(parse_field(raw_name[1..].as_bytes(), 10)) as usize;
+                match names {
+                    Some((n_start, n_end)) => Some(try!(extended_name(&data[n_start..n_end], name_off))),
+                    None => try!(Err(Error::from("ar member refers to a missing name table"))),
+                }
+            } else {
+                Some(String::from(raw_name.trim_end_matches('/')))
+            };
+
+            if let Some(name) = name {
+                members.push(Member {
+                    hdr: types::MemberHeader {
+                        name: name,
+                        date: date,
+                        uid: uid,
+                        gid: gid,
+                        mode: mode,
+                        size: size,
+                    },
+                    offset: start,
+                });
+            }
+
+            // Member data is padded to an even offset
+            off = end + (end & 1);
+        }
+
+        Ok(Archive {
+            data: data,
+            members: members,
+        })
+    }
+    /// Member names and contents, in archive order (symbol and name tables excluded)
+    pub fn members(&self) -> Vec<(String, &[u8])> {
+        self.members.iter()
+            .map(|m| (m.hdr.name.clone(), &self.data[m.offset..m.offset + m.hdr.size as usize]))
+            .collect()
+    }
+    pub fn member_headers(&self) -> Vec<&types::MemberHeader> {
+        self.members.iter().map(|m| &m.hdr).collect()
+    }
+    /// Parse each member with the format auto-detector
+    pub fn objects(&self) -> Vec<(String, Result<Box<Object>, Error>)> {
+        self.members().into_iter()
+            .map(|(name, data)| (name, ::parse(&mut io::Cursor::new(data))))
+            .collect()
+    }
+}
@@ -0,0 +1,4 @@
+pub mod file;
+pub mod types;
+
+pub use archive::file::Archive;
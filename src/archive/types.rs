@@ -0,0 +1,24 @@
+/// Global header at the start of every archive
+pub static ARMAG: &'static [u8; 8] = b"!<arch>\n";
+/// Terminator of each member header
+pub static ARFMAG: &'static [u8; 2] = b"`\n";
+/// Size of a member header
+pub const AR_HDR_SIZE: usize = 60;
+
+/// GNU/SysV symbol table member
+pub const AR_SYMTAB: &'static str = "/";
+/// GNU/SysV symbol table member with 64-bit offsets
+pub const AR_SYMTAB64: &'static str = "/SYM64/";
+/// GNU/SysV extended (long) name table member
+pub const AR_NAMES: &'static str = "//";
+/// BSD prefix for a name stored after the header, followed by its length
+pub const AR_BSD_NAME: &'static str = "#1/";
+
+pub struct MemberHeader {
+    pub name: String,
+    pub date: u64,
+    pub uid: u32,
+    pub gid: u32,
+    pub mode: u32,
+    pub size: u64,
+}
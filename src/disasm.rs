@@ -0,0 +1,110 @@
+//! Disassembly of section contents, using capstone
+
+use capstone;
+use capstone::Capstone;
+use {Arch, ARMMode, ARMType, Endianness, Error, Width};
+
+/// A decoded instruction
+#[derive(Clone, Debug)]
+pub struct Insn {
+    pub address: u64,
+    pub bytes: Vec<u8>,
+    pub mnemonic: String,
+    pub op_str: String,
+}
+
+fn capstone_error(e: capstone::Error) -> Error {
+    Error::Other(format!("capstone: {}", e))
+}
+
+impl Arch {
+    /// The capstone architecture to disassemble for, or `None` if capstone
+    /// doesn't support it
+    pub fn capstone_arch(&self) -> Option<capstone::Arch> {
+        Some(match *self {
+            Arch::X86(_) => capstone::Arch::X86,
+            Arch::ARM(Width::W64, _, _, _) => capstone::Arch::ARM64,
+            Arch::ARM(_, _, _, _) => capstone::Arch::ARM,
+            Arch::PPC(_, _) => capstone::Arch::PPC,
+            Arch::MIPS(_, _) => capstone::Arch::MIPS,
+            Arch::SPARC(_, _) => capstone::Arch::SPARC,
+            Arch::RISCV(_, _) => capstone::Arch::RISCV,
+            Arch::S390(_, _) => capstone::Arch::SYSZ,
+            Arch::Wasm | Arch::Unknown => return None,
+        })
+    }
+
+    /// The capstone mode to go with `capstone_arch`
+    ///
+    /// 32-bit ARM gets `Arm` or `Thumb` from its `ARMMode`; AArch64 has only
+    /// the one mode, whatever `ARMMode` says.
+    pub fn capstone_mode(&self) -> Option<capstone::Mode> {
+        Some(match *self {
+            Arch::X86(Width::W16) => capstone::Mode::Mode16,
+            Arch::X86(Width::W32) => capstone::Mode::Mode32,
+            Arch::X86(Width::W64) => capstone::Mode::Mode64,
+            Arch::ARM(Width::W64, _, _, _) => capstone::Mode::Arm,
+            Arch::ARM(_, _, ARMMode::ARM, _) => capstone::Mode::Arm,
+            Arch::ARM(_, _, ARMMode::Thumb, _) => capstone::Mode::Thumb,
+            Arch::PPC(Width::W64, _) => capstone::Mode::Mode64,
+            Arch::PPC(_, _) => capstone::Mode::Mode32,
+            Arch::MIPS(Width::W64, _) => capstone::Mode::Mips64,
+            Arch::MIPS(_, _) => capstone::Mode::Mips32,
+            Arch::SPARC(Width::W64, _) => capstone::Mode::V9,
+            Arch::SPARC(_, _) => capstone::Mode::Default,
+            Arch::RISCV(Width::W64, _) => capstone::Mode::RiscV64,
+            Arch::RISCV(_, _) => capstone::Mode::RiscV32,
+            Arch::S390(_, _) => capstone::Mode::Default,
+            Arch::Wasm | Arch::Unknown => return None,
+        })
+    }
+
+    /// Extra capstone modes: M-profile or ARMv8 for 32-bit ARM, and
+    /// compressed instructions for RISC-V (which are near universal)
+    pub fn capstone_extra_modes(&self) -> Vec<capstone::ExtraMode> {
+        match *self {
+            Arch::ARM(Width::W64, _, _, _) => Vec::new(),
+            Arch::ARM(_, _, _, ARMType::MClass) => vec![capstone::ExtraMode::MClass],
+            Arch::ARM(_, _, _, ARMType::V8) => vec![capstone::ExtraMode::V8],
+            Arch::RISCV(_, _) => vec![capstone::ExtraMode::RiscVC],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Byte order to give capstone, or `None` for x86 (always little endian)
+    pub fn capstone_endian(&self) -> Option<capstone::Endian> {
+        let e = match *self {
+            Arch::ARM(_, e, _, _) | Arch::PPC(_, e) | Arch::MIPS(_, e) |
+            Arch::SPARC(_, e) | Arch::RISCV(_, e) | Arch::S390(_, e) => e,
+            _ => return None,
+        };
+        Some(match e {
+            Endianness::Little => capstone::Endian::Little,
+            Endianness::Big => capstone::Endian::Big,
+        })
+    }
+}
+
+fn capstone_for(arch: Arch) -> Result<Capstone, Error> {
+    let (cs_arch, mode) = match (arch.capstone_arch(), arch.capstone_mode()) {
+        (Some(a), Some(m)) => (a, m),
+        _ => return Err(match arch {
+            Arch::Wasm => Error::from("cannot disassemble WebAssembly"),
+            _ => Error::from("cannot disassemble for an unknown architecture"),
+        }),
+    };
+    Capstone::new_raw(cs_arch, mode, arch.capstone_extra_modes().into_iter(), arch.capstone_endian()).map_err(capstone_error)
+}
+
+/// Disassemble `code`, which is loaded at `addr`, stopping at the first
+/// invalid instruction
+pub fn disassemble(arch: Arch, code: &[u8], addr: u64) -> Result<Vec<Insn>, Error> {
+    let cs = try!(capstone_for(arch));
+    let insns = try!(cs.disasm_all(code, addr).map_err(capstone_error));
+    Ok(insns.iter().map(|i| Insn {
+        address: i.address(),
+        bytes: i.bytes().to_vec(),
+        mnemonic: String::from(i.mnemonic().unwrap_or("")),
+        op_str: String::from(i.op_str().unwrap_or("")),
+    }).collect())
+}
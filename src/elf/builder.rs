@@ -0,0 +1,316 @@
+use std::vec::Vec;
+use std::string::String;
+use elf::types;
+use {Arch, ARMMode, Endianness, Error, Width};
+
+/// Alignment of the `PT_LOAD` segments, and so of each loaded section's
+/// file offset relative to its address
+const PAGE_SIZE: u64 = 0x1000;
+
+/// Sequential writer in the file's byte order
+struct Writer {
+    buf: Vec<u8>,
+    class: types::Class,
+    data: types::Data,
+}
+
+impl Writer {
+    fn u8(&mut self, x: u8) {
+        self.buf.push(x);
+    }
+    fn u16(&mut self, x: u16) {
+        match self.data {
+            types::ELFDATA2MSB => { self.buf.push((x >> 8) as u8); self.buf.push(x as u8); },
+            _ => { self.buf.push(x as u8); self.buf.push((x >> 8) as u8); },
+        }
+    }
+    fn u32(&mut self, x: u32) {
+        match self.data {
+            types::ELFDATA2MSB => { self.u16((x >> 16) as u16); self.u16(x as u16); },
+            _ => { self.u16(x as u16); self.u16((x >> 16) as u16); },
+        }
+    }
+    fn u64(&mut self, x: u64) {
+        match self.data {
+            types::ELFDATA2MSB => { self.u32((x >> 32) as u32); self.u32(x as u32); },
+            _ => { self.u32(x as u32); self.u32((x >> 32) as u32); },
+        }
+    }
+    /// A 32 or 64-bit word depending on the file class
+    fn word(&mut self, x: u64) {
+        match self.class {
+            types::ELFCLASS32 => self.u32(x as u32),
+            _ => self.u64(x),
+        }
+    }
+    /// Zero-fill up to `offset`
+    fn pad_to(&mut self, offset: u64) {
+        let len = offset as usize;
+        if self.buf.len() < len {
+            self.buf.resize(len, 0);
+        }
+    }
+}
+
+struct BuilderSection {
+    name: String,
+    flags: types::SectionFlag,
+    addr: u64,
+    data: Vec<u8>,
+}
+
+/// Lays out a minimal executable ELF from a list of sections
+///
+/// Every `SHF_ALLOC` section gets a `PT_LOAD` segment of its own (readable,
+/// plus writable/executable per `SHF_WRITE`/`SHF_EXECINSTR`), placed in the
+/// file so its offset and address agree modulo the page size. A
+/// `.shstrtab` is added after the given sections.
+pub struct ElfBuilder {
+    arch: Arch,
+    entry: u64,
+    sections: Vec<BuilderSection>,
+}
+
+impl ElfBuilder {
+    /// A builder for an x86-64 executable with an entry point of 0
+    pub fn new() -> ElfBuilder {
+        ElfBuilder {
+            arch: Arch::X86(Width::W64),
+            entry: 0,
+            sections: Vec::new(),
+        }
+    }
+
+    /// Set the machine, class and byte order of the file
+    pub fn set_arch(&mut self, arch: Arch) -> &mut ElfBuilder {
+        self.arch = arch;
+        self
+    }
+
+    pub fn set_entry(&mut self, entry: u64) -> &mut ElfBuilder {
+        self.entry = entry;
+        self
+    }
+
+    pub fn add_section(&mut self, name: &str, flags: types::SectionFlag, addr: u64, data: &[u8]) -> &mut ElfBuilder {
+        self.sections.push(BuilderSection {
+            name: String::from(name),
+            flags: flags,
+            addr: addr,
+            data: data.to_vec(),
+        });
+        self
+    }
+
+    fn machine(&self) -> (types::Machine, types::Class, types::Data) {
+        let class = |w: Width| match w {
+            Width::W64 => types::ELFCLASS64,
+            _ => types::ELFCLASS32,
+        };
+        let data = |e: Endianness| match e {
+            Endianness::Little => types::ELFDATA2LSB,
+            Endianness::Big => types::ELFDATA2MSB,
+        };
+        match self.arch {
+            Arch::X86(Width::W64) => (types::EM_X86_64, types::ELFCLASS64, types::ELFDATA2LSB),
+            Arch::X86(_) => (types::EM_386, types::ELFCLASS32, types::ELFDATA2LSB),
+            Arch::ARM(Width::W64, e, _, _) => (types::EM_AARCH64, types::ELFCLASS64, data(e)),
+            Arch::ARM(_, e, _, _) => (types::EM_ARM, types::ELFCLASS32, data(e)),
+            Arch::PPC(Width::W64, e) => (types::EM_PPC64, types::ELFCLASS64, data(e)),
+            Arch::PPC(_, e) => (types::EM_PPC, types::ELFCLASS32, data(e)),
+            Arch::MIPS(w, e) => (types::EM_MIPS, class(w), data(e)),
+            Arch::SPARC(Width::W64, e) => (types::EM_SPARCV9, types::ELFCLASS64, data(e)),
+            Arch::SPARC(_, e) => (types::EM_SPARC, types::ELFCLASS32, data(e)),
+            Arch::RISCV(w, e) => (types::EM_RISCV, class(w), data(e)),
+            Arch::S390(w, e) => (types::EM_S390, class(w), data(e)),
+            Arch::Wasm | Arch::Unknown => (types::EM_NONE, types::ELFCLASS64, types::ELFDATA2LSB),
+        }
+    }
+
+    /// `e_flags` and entry point as written: 32-bit ARM files are marked
+    /// EABI version 5, and a Thumb entry point gets its low bit set
+    fn flags_and_entry(&self) -> (u32, u64) {
+        match self.arch {
+            Arch::ARM(Width::W64, _, _, _) => (0, self.entry),
+            Arch::ARM(_, _, ARMMode::Thumb, _) => (types::EF_ARM_EABI_VER5, self.entry | 1),
+            Arch::ARM(_, _, ARMMode::ARM, _) => (types::EF_ARM_EABI_VER5, self.entry),
+            _ => (0, self.entry),
+        }
+    }
+
+    /// Lay out the file: ELF header, program headers, section contents,
+    /// section name table and finally the section header table
+    ///
+    /// Fails if there are more sections than fit in `e_shnum` (with the
+    /// null section and `.shstrtab` added, no more than `0xfeff`).
+    pub fn build(&self) -> Result<Vec<u8>, Error> {
+        let shnum = self.sections.len() + 2;
+        if shnum >= types::SHN_LORESERVE as usize {
+            return Err(Error::from("too many sections for the ELF header"));
+        }
+        let shnum = shnum as u16;
+        let (machine, class, data) = self.machine();
+        let (flags, entry) = self.flags_and_entry();
+        let (ehsize, phentsize, shentsize) = match class {
+            types::ELFCLASS32 => (52u64, 32u64, 40u64),
+            _ => (64u64, 56u64, 64u64),
+        };
+        let loads: Vec<&BuilderSection> = self.sections.iter().filter(|s| s.flags.contains(types::SHF_ALLOC)).collect();
+
+        let mut shstrtab = Vec::new();
+        shstrtab.push(0u8);
+        let mut name_idxs = Vec::new();
+        for section in self.sections.iter() {
+            name_idxs.push(shstrtab.len() as u32);
+            shstrtab.extend(section.name.bytes());
+            shstrtab.push(0);
+        }
+        let shstrtab_name = shstrtab.len() as u32;
+        shstrtab.extend(b".shstrtab\0".iter());
+
+        // Section offsets, congruent to their addresses if they're loaded
+        let mut offsets = Vec::new();
+        let mut off = ehsize + phentsize * loads.len() as u64;
+        for section in self.sections.iter() {
+            if section.flags.contains(types::SHF_ALLOC) {
+                let want = section.addr % PAGE_SIZE;
+                off += (want + PAGE_SIZE - off % PAGE_SIZE) % PAGE_SIZE;
+            }
+            offsets.push(off);
+            off += section.data.len() as u64;
+        }
+        let shstrtab_off = off;
+        let shoff = (shstrtab_off + shstrtab.len() as u64 + 7) & !7;
+
+        let mut w = Writer { buf: Vec::new(), class: class, data: data };
+        w.buf.extend(types::ELFMAG.iter());
+        w.u8(class.0);
+        w.u8(data.0);
+        w.u8(types::EV_CURRENT.0 as u8);
+        w.u8(types::ELFOSABI_NONE.0);
+        w.pad_to(types::EI_NIDENT as u64);
+        w.u16(types::ET_EXEC.0);
+        w.u16(machine.0);
+        w.u32(types::EV_CURRENT.0);
+        w.word(entry);
+        w.word(if loads.is_empty() { 0 } else { ehsize });
+        w.word(shoff);
+        w.u32(flags);
+        w.u16(ehsize as u16);
+        w.u16(if loads.is_empty() { 0 } else { phentsize as u16 });
+        // No more than the sections, so this can't truncate
+        w.u16(loads.len() as u16);
+        w.u16(shentsize as u16);
+        w.u16(shnum);
+        w.u16(shnum - 1);
+
+        for (section, &offset) in self.sections.iter().zip(offsets.iter()) {
+            if !section.flags.contains(types::SHF_ALLOC) {
+                continue;
+            }
+            let mut flags = types::PF_R.0;
+            if section.flags.contains(types::SHF_WRITE) {
+                flags |= types::PF_W.0;
+            }
+            if section.flags.contains(types::SHF_EXECINSTR) {
+                flags |= types::PF_X.0;
+            }
+            let size = section.data.len() as u64;
+            w.u32(types::PT_LOAD.0);
+            if class == types::ELFCLASS64 {
+                w.u32(flags);
+            }
+            w.word(offset);
+            w.word(section.addr);
+            w.word(section.addr);
+            w.word(size);
+            w.word(size);
+            if class == types::ELFCLASS32 {
+                w.u32(flags);
+            }
+            w.word(PAGE_SIZE);
+        }
+
+        for (section, &offset) in self.sections.iter().zip(offsets.iter()) {
+            w.pad_to(offset);
+            w.buf.extend(section.data.iter());
+        }
+        w.pad_to(shstrtab_off);
+        w.buf.extend(shstrtab.iter());
+        w.pad_to(shoff);
+
+        // Null section, the given sections, then .shstrtab
+        for _ in 0..shentsize {
+            w.u8(0);
+        }
+        for ((section, &offset), &name_idx) in self.sections.iter().zip(offsets.iter()).zip(name_idxs.iter()) {
+            w.u32(name_idx);
+            w.u32(types::SHT_PROGBITS.0);
+            w.word(section.flags.0);
+            w.word(section.addr);
+            w.word(offset);
+            w.word(section.data.len() as u64);
+            w.u32(0);
+            w.u32(0);
+            w.word(1);
+            w.word(0);
+        }
+        w.u32(shstrtab_name);
+        w.u32(types::SHT_STRTAB.0);
+        w.word(0);
+        w.word(0);
+        w.word(shstrtab_off);
+        w.word(shstrtab.len() as u64);
+        w.u32(0);
+        w.u32(0);
+        w.word(1);
+        w.word(0);
+        Ok(w.buf)
+    }
+}
+
+impl Default for ElfBuilder {
+    fn default() -> ElfBuilder {
+        ElfBuilder::new()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use elf::File;
+    use Object;
+
+    #[test]
+    fn build_then_parse_round_trips() {
+        let text = [0x90u8; 16];
+        let data = [1u8, 2, 3, 4];
+        let bytes = ElfBuilder::new()
+            .set_entry(0x401000)
+            .add_section(".text", types::SectionFlag(types::SHF_ALLOC.0 | types::SHF_EXECINSTR.0), 0x401000, &text)
+            .add_section(".data", types::SectionFlag(types::SHF_ALLOC.0 | types::SHF_WRITE.0), 0x402000, &data)
+            .build()
+            .unwrap();
+        let file = File::parse_bytes(&bytes).unwrap();
+        assert_eq!(file.entry(), 0x401000);
+        assert_eq!(file.arch(), Arch::X86(Width::W64));
+        let names: Vec<&str> = file.sections().iter().map(|s| s.name()).collect();
+        assert_eq!(names, ["", ".text", ".data", ".shstrtab"]);
+        let text_section = file.get_section(".text").unwrap();
+        assert_eq!(text_section.addr(), 0x401000);
+        assert_eq!(text_section.data(), &text[..]);
+        assert!(text_section.is_executable());
+        assert_eq!(file.get_section(".data").unwrap().data(), &data[..]);
+        assert_eq!(file.segments().len(), 2);
+        assert!(file.verify_loadable().is_ok());
+    }
+
+    #[test]
+    fn build_rejects_too_many_sections() {
+        let mut builder = ElfBuilder::default();
+        for _ in 0..0xfefe {
+            builder.add_section(".x", types::SectionFlag(0), 0, &[]);
+        }
+        assert!(builder.build().is_err());
+    }
+}
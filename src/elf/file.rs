@@ -1,12 +1,17 @@
 use std::io::prelude::*;
 use std::io;
 use std::fmt;
-use std::error;
+use std::cmp;
+use std::convert::TryFrom;
 use byteorder;
-use byteorder::ReadBytesExt;
+use byteorder::{ReadBytesExt, WriteBytesExt};
 use elf::types;
+use elf::header;
+use source::IoSource;
 use std::collections::HashMap;
 use {Error, Section, Object};
+#[cfg(feature = "serde")]
+use serde::Serialize;
 
 macro_rules! read_u8 {
     ($data:ident, $io:ident) => (
@@ -19,7 +24,7 @@ macro_rules! read_u16 {
         match $data {
             types::ELFDATA2LSB => { $io.read_u16::<byteorder::LittleEndian>() },
             types::ELFDATA2MSB => { $io.read_u16::<byteorder::BigEndian>()},
-            _ => { try!(Err(Error::from("invalid endianness"))) },
+            _ => { try!(Err(Error::InvalidEndianness)) },
         }
     );
 }
@@ -29,7 +34,7 @@ macro_rules! read_u32 {
         match $data {
             types::ELFDATA2LSB => { $io.read_u32::<byteorder::LittleEndian>() },
             types::ELFDATA2MSB => { $io.read_u32::<byteorder::BigEndian>()},
-            _ => { try!(Err(Error::from("invalid endianness"))) },
+            _ => { try!(Err(Error::InvalidEndianness)) },
         }
     );
 }
@@ -39,205 +44,2436 @@ macro_rules! read_u64 {
         match $data {
             types::ELFDATA2LSB => { $io.read_u64::<byteorder::LittleEndian>() },
             types::ELFDATA2MSB => { $io.read_u64::<byteorder::BigEndian>()},
-            _ => { try!(Err(Error::from("invalid endianness"))) },
+            _ => { try!(Err(Error::InvalidEndianness)) },
         }
     );
 }
 
-fn get_elf_string(data: &Vec<u8>, start: usize) -> String {
-    let mut end = 0usize;
-    for i in start..data.len() {
-        if data[i] == 0u8 {
-            end = i;
+macro_rules! write_u16 {
+    ($data:ident, $io:ident, $val:expr) => (
+        match $data {
+            types::ELFDATA2LSB => { $io.write_u16::<byteorder::LittleEndian>($val) },
+            types::ELFDATA2MSB => { $io.write_u16::<byteorder::BigEndian>($val) },
+            _ => { try!(Err(Error::InvalidEndianness)) },
+        }
+    );
+}
+
+macro_rules! write_u32 {
+    ($data:ident, $io:ident, $val:expr) => (
+        match $data {
+            types::ELFDATA2LSB => { $io.write_u32::<byteorder::LittleEndian>($val) },
+            types::ELFDATA2MSB => { $io.write_u32::<byteorder::BigEndian>($val) },
+            _ => { try!(Err(Error::InvalidEndianness)) },
+        }
+    );
+}
+
+macro_rules! write_u64 {
+    ($data:ident, $io:ident, $val:expr) => (
+        match $data {
+            types::ELFDATA2LSB => { $io.write_u64::<byteorder::LittleEndian>($val) },
+            types::ELFDATA2MSB => { $io.write_u64::<byteorder::BigEndian>($val) },
+            _ => { try!(Err(Error::InvalidEndianness)) },
+        }
+    );
+}
+
+/// A 32 or 64-bit word depending on the file class
+macro_rules! write_word {
+    ($class:ident, $data:ident, $io:ident, $val:expr) => (
+        match $class {
+            types::ELFCLASS32 => write_u32!($data, $io, $val as u32),
+            _ => write_u64!($data, $io, $val),
+        }
+    );
+}
+
+/// Offset of `name` in a string table, the inverse of `header::table_string`
+///
+/// Linkers share the tails of strings (`.rela.plt` also provides `.plt`),
+/// so a string of its own is preferred, then `name` and its NUL anywhere.
+fn find_elf_string(data: &[u8], name: &str) -> Option<usize> {
+    let mut needle = vec![0u8];
+    needle.extend(name.as_bytes());
+    needle.push(0);
+    if !name.is_empty() {
+        if let Some(pos) = data.windows(needle.len()).position(|w| w == &needle[..]) {
+            return Some(pos + 1);
+        }
+    }
+    data.windows(needle.len() - 1).position(|w| w == &needle[1..])
+}
+
+fn parse_symbols(class: types::Class, data: types::Data, section: &types::SectionHeader, section_data: &[u8], strtab: &[u8]) -> Result<Vec<types::Symbol>, Error> {
+    let mut symbols = Vec::new();
+
+    if section.entsize == 0 || section.size % section.entsize != 0 {
+        try!(Err(Error::InvalidEntsize));
+    }
+    let mut cur = io::Cursor::new(section_data);
+    for i in 0..(section.size / section.entsize) {
+        try!(cur.seek(io::SeekFrom::Start(i * section.entsize)));
+        let sym_name;
+        let sym_value;
+        let sym_size;
+        let sym_info;
+        let sym_other;
+        let sym_shndx;
+        match class {
+            types::ELFCLASS32 => {
+                sym_name = try!(read_u32!(data, cur));
+                sym_value = try!(read_u32!(data, cur)) as u64;
+                sym_size = try!(read_u32!(data, cur)) as u64;
+                sym_info = try!(read_u8!(data, cur));
+                sym_other = try!(read_u8!(data, cur));
+                sym_shndx = try!(read_u16!(data, cur));
+            }
+            types::ELFCLASS64 => {
+                sym_name = try!(read_u32!(data, cur));
+                sym_info = try!(read_u8!(data, cur));
+                sym_other = try!(read_u8!(data, cur));
+                sym_shndx = try!(read_u16!(data, cur));
+                sym_value = try!(read_u64!(data, cur));
+                sym_size = try!(read_u64!(data, cur));
+            }
+            _ => unreachable!(),
+        }
+        symbols.push(types::Symbol {
+            name: try!(header::table_string(strtab, sym_name as usize)),
+            value: sym_value,
+            size: sym_size,
+            bind: types::SymbolBind(sym_info >> 4),
+            sym_type: types::SymbolType(sym_info & 0xf),
+            vis: types::SymbolVis(sym_other & 0x3),
+            shndx: sym_shndx,
+            version: None,
+            version_hidden: false,
+        });
+    }
+
+    Ok(symbols)
+}
+
+fn parse_relocations(class: types::Class, data: types::Data, section: &types::SectionHeader, section_data: &[u8]) -> Result<Vec<types::Rela>, Error> {
+    let mut relocs = Vec::new();
+
+    if section.entsize == 0 || section.size % section.entsize != 0 {
+        try!(Err(Error::InvalidEntsize));
+    }
+    let mut cur = io::Cursor::new(section_data);
+    for i in 0..(section.size / section.entsize) {
+        try!(cur.seek(io::SeekFrom::Start(i * section.entsize)));
+        let offset;
+        let sym;
+        let rel_type;
+        let mut addend = 0i64;
+        match class {
+            types::ELFCLASS32 => {
+                offset = try!(read_u32!(data, cur)) as u64;
+                let info = try!(read_u32!(data, cur));
+                sym = info >> 8;
+                rel_type = info & 0xff;
+                if section.shtype == types::SHT_RELA {
+                    addend = try!(read_u32!(data, cur)) as i32 as i64;
+                }
+            }
+            types::ELFCLASS64 => {
+                offset = try!(read_u64!(data, cur));
+                let info = try!(read_u64!(data, cur));
+                sym = (info >> 32) as u32;
+                rel_type = (info & 0xffffffff) as u32;
+                if section.shtype == types::SHT_RELA {
+                    addend = try!(read_u64!(data, cur)) as i64;
+                }
+            }
+            _ => unreachable!(),
+        }
+        if section.shtype == types::SHT_RELA {
+            relocs.push(types::Rela {
+                offset: offset,
+                sym: sym,
+                rel_type: rel_type,
+                addend: addend,
+            });
+        } else {
+            relocs.push(types::Rela::from(types::Rel {
+                offset: offset,
+                sym: sym,
+                rel_type: rel_type,
+            }));
+        }
+    }
+
+    Ok(relocs)
+}
+
+fn parse_dynamic(class: types::Class, data: types::Data, section_data: &[u8]) -> Result<Vec<types::DynEntry>, Error> {
+    let mut entries = Vec::new();
+
+    let entsize = match class {
+        types::ELFCLASS32 => 8,
+        types::ELFCLASS64 => 16,
+        _ => unreachable!(),
+    };
+    let mut cur = io::Cursor::new(section_data);
+    for _ in 0..(section_data.len() / entsize) {
+        let tag;
+        let value;
+        match class {
+            types::ELFCLASS32 => {
+                tag = types::DynTag(try!(read_u32!(data, cur)) as u64);
+                value = try!(read_u32!(data, cur)) as u64;
+            }
+            types::ELFCLASS64 => {
+                tag = types::DynTag(try!(read_u64!(data, cur)));
+                value = try!(read_u64!(data, cur));
+            }
+            _ => unreachable!(),
+        }
+        if tag == types::DT_NULL {
+            break;
+        }
+        entries.push(types::DynEntry {
+            tag: tag,
+            value: value,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Version names by index from an `SHT_GNU_verneed` section
+fn parse_verneed(data: types::Data, section: &types::SectionHeader, section_data: &[u8], strtab: &[u8]) -> Result<HashMap<u16, String>, Error> {
+    let mut versions = HashMap::new();
+
+    let mut cur = io::Cursor::new(section_data);
+    let mut off = 0u64;
+    // sh_info holds the number of Elf_Verneed entries
+    for _ in 0..section.info {
+        try!(cur.seek(io::SeekFrom::Start(off)));
+        let _vn_version = try!(read_u16!(data, cur));
+        let vn_cnt = try!(read_u16!(data, cur));
+        let _vn_file = try!(read_u32!(data, cur));
+        let vn_aux = try!(read_u32!(data, cur));
+        let vn_next = try!(read_u32!(data, cur));
+
+        let mut aux_off = off + vn_aux as u64;
+        for _ in 0..vn_cnt {
+            try!(cur.seek(io::SeekFrom::Start(aux_off)));
+            let _vna_hash = try!(read_u32!(data, cur));
+            let _vna_flags = try!(read_u16!(data, cur));
+            let vna_other = try!(read_u16!(data, cur));
+            let vna_name = try!(read_u32!(data, cur));
+            let vna_next = try!(read_u32!(data, cur));
+            versions.insert(vna_other & types::VERSYM_VERSION, try!(header::table_string(strtab, vna_name as usize)));
+            if vna_next == 0 {
+                break;
+            }
+            aux_off += vna_next as u64;
+        }
+        if vn_next == 0 {
+            break;
+        }
+        off += vn_next as u64;
+    }
+
+    Ok(versions)
+}
+
+/// Version names by index from an `SHT_GNU_verdef` section, leaving out
+/// the `VER_FLG_BASE` entry that names the file itself
+fn parse_verdef(data: types::Data, section: &types::SectionHeader, section_data: &[u8], strtab: &[u8]) -> Result<HashMap<u16, String>, Error> {
+    let mut versions = HashMap::new();
+
+    let mut cur = io::Cursor::new(section_data);
+    let mut off = 0u64;
+    // sh_info holds the number of Elf_Verdef entries
+    for _ in 0..section.info {
+        try!(cur.seek(io::SeekFrom::Start(off)));
+        let _vd_version = try!(read_u16!(data, cur));
+        let vd_flags = try!(read_u16!(data, cur));
+        let vd_ndx = try!(read_u16!(data, cur));
+        let vd_cnt = try!(read_u16!(data, cur));
+        let _vd_hash = try!(read_u32!(data, cur));
+        let vd_aux = try!(read_u32!(data, cur));
+        let vd_next = try!(read_u32!(data, cur));
+
+        // The first Elf_Verdaux holds the version's own name, any others
+        // name its predecessors
+        if vd_cnt > 0 && vd_flags & types::VER_FLG_BASE == 0 {
+            try!(cur.seek(io::SeekFrom::Start(off + vd_aux as u64)));
+            let vda_name = try!(read_u32!(data, cur));
+            versions.insert(vd_ndx & types::VERSYM_VERSION, try!(header::table_string(strtab, vda_name as usize)));
+        }
+        if vd_next == 0 {
             break;
         }
+        off += vd_next as u64;
+    }
+
+    Ok(versions)
+}
+
+/// Attach versions from an `SHT_GNU_versym` section, which has one entry
+/// per dynamic symbol, to `symbols`
+fn apply_versym(data: types::Data, section_data: &[u8], versions: &HashMap<u16, String>, symbols: &mut [types::Symbol], warnings: &mut Vec<types::Warning>) -> Result<(), Error> {
+    let mut cur = io::Cursor::new(section_data);
+    for (i, sym) in symbols.iter_mut().take(section_data.len() / 2).enumerate() {
+        let versym = try!(read_u16!(data, cur));
+        let index = versym & types::VERSYM_VERSION;
+        if index == types::VER_NDX_LOCAL || index == types::VER_NDX_GLOBAL {
+            continue;
+        }
+        sym.version = versions.get(&index).cloned();
+        if sym.version.is_none() {
+            warnings.push(types::Warning::UnknownSymbolVersion { symbol: i, version: index });
+        }
+        sym.version_hidden = versym & types::VERSYM_HIDDEN != 0;
+    }
+
+    Ok(())
+}
+
+/// Contents of the section named by `section`'s `sh_link`, such as the
+/// string table of a symbol table
+fn linked_data<'a>(sections_data: &'a [Vec<u8>], section: &types::SectionHeader) -> Result<&'a Vec<u8>, Error> {
+    match sections_data.get(section.link as usize) {
+        Some(data) => Ok(data),
+        None => Err(Error::Other(format!("section {} links to nonexistent section {}", section.name, section.link))),
+    }
+}
+
+/// Sections `parse_lazy` still reads because `File` accessors decode them
+/// (`eh_frame_hdr`, `debuglink`, `debugaltlink`, `interpreter`,
+/// `arm_attributes` and `plt_entries`), so that those don't quietly come
+/// back empty; all of them are small
+fn needed_by_accessors(section: &types::SectionHeader) -> bool {
+    section.shtype == types::SHT_ARM_ATTRIBUTES || matches!(&section.name[..],
+        ".eh_frame_hdr" | ".gnu_debuglink" | ".gnu_debugaltlink" | ".interp" | ".plt" | ".plt.sec")
+}
+
+/// Contents of each section for which `needed` is set (empty otherwise),
+/// after checking that every section lies within the file
+fn read_sections<R: io::Read + io::Seek>(r: &mut R, sections: &[types::SectionHeader], needed: &[bool], file_len: u64) -> Result<Vec<Vec<u8>>, Error> {
+    let mut sections_data = Vec::new();
+    for (section, &needed) in sections.iter().zip(needed.iter()) {
+        let off = section.offset;
+        let size = section.size;
+        // Section 0's size field may hold the extended section count
+        if section.shtype == types::SHT_NOBITS || section.shtype == types::SHT_NULL {
+            sections_data.push(Vec::new());
+            continue;
+        }
+        match off.checked_add(size) {
+            Some(end) if end <= file_len => {}
+            _ => try!(Err(Error::Truncated { offset: off })),
+        }
+        if !needed {
+            sections_data.push(Vec::new());
+            continue;
+        }
+        try!(r.seek(io::SeekFrom::Start(off)));
+        let mut data = vec![0; size as usize];
+        try!(r.read_exact(&mut data));
+        sections_data.push(data);
+    }
+    Ok(sections_data)
+}
+
+/// `read_sections` for input already in memory, copying the sections out
+/// in parallel
+///
+/// Errors are reported for the first bad section in table order, as in
+/// the sequential path.
+#[cfg(feature = "rayon")]
+fn read_sections_par(sections: &[types::SectionHeader], needed: &[bool], input: &[u8]) -> Result<Vec<Vec<u8>>, Error> {
+    use rayon::prelude::*;
+
+    let results: Vec<Result<Vec<u8>, Error>> = sections.par_iter().zip(needed.par_iter()).map(|(section, &needed)| {
+        if section.shtype == types::SHT_NOBITS || section.shtype == types::SHT_NULL {
+            return Ok(Vec::new());
+        }
+        let end = match section.offset.checked_add(section.size) {
+            Some(end) if end <= input.len() as u64 => end as usize,
+            _ => return Err(Error::Truncated { offset: section.offset }),
+        };
+        if !needed {
+            return Ok(Vec::new());
+        }
+        Ok(input[section.offset as usize..end].to_vec())
+    }).collect();
+    results.into_iter().collect()
+}
+
+/// Inflate an `SHF_COMPRESSED` section, returning `None` for unsupported `ch_type`s
+#[cfg(feature = "compression")]
+fn decompress_section(class: types::Class, data: types::Data, section_data: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+    let mut cur = io::Cursor::new(section_data);
+    let ch_type = try!(read_u32!(data, cur));
+    let ch_size = match class {
+        types::ELFCLASS32 => try!(read_u32!(data, cur)) as u64,
+        types::ELFCLASS64 => {
+            // ch_reserved
+            try!(read_u32!(data, cur));
+            try!(read_u64!(data, cur))
+        },
+        _ => unreachable!(),
+    };
+    if ch_type != types::ELFCOMPRESS_ZLIB {
+        return Ok(None);
+    }
+    let hdr_size = match class {
+        types::ELFCLASS32 => 12,
+        _ => 24,
+    };
+    if section_data.len() < hdr_size {
+        return Err(Error::Truncated { offset: section_data.len() as u64 });
+    }
+    inflate(&section_data[hdr_size..], ch_size).map(Some)
+}
+
+/// Inflate a GNU-style `.zdebug_*` section (`ZLIB` magic, then a big-endian size)
+#[cfg(feature = "compression")]
+fn decompress_zdebug(section_data: &[u8]) -> Result<Vec<u8>, Error> {
+    if section_data.len() < 12 || &section_data[..4] != types::ZDEBUG_MAGIC {
+        try!(Err(Error::from("invalid .zdebug section header")));
+    }
+    let mut cur = io::Cursor::new(&section_data[4..12]);
+    let size = try!(cur.read_u64::<byteorder::BigEndian>());
+    inflate(&section_data[12..], size)
+}
+
+/// Whether `sym` demangles to exactly `name`
+#[cfg(feature = "demangle")]
+fn demangled_is(sym: &types::Symbol, name: &str) -> bool {
+    !sym.name.is_empty() && sym.demangled() == name
+}
+
+#[cfg(not(feature = "demangle"))]
+fn demangled_is(_sym: &types::Symbol, _name: &str) -> bool {
+    false
+}
+
+/// Leading bytes of a gzip stream
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+/// Leading bytes of an xz stream
+const XZ_MAGIC: [u8; 6] = [0xfd, b'7', b'z', b'X', b'Z', 0];
+
+/// Largest decompressed input `File::parse_compressed` will buffer (1 GiB)
+pub const DEFAULT_MAX_DECOMPRESSED_LEN: u64 = 1 << 30;
+
+#[cfg(feature = "compression")]
+fn gunzip(input: &[u8], max_len: u64) -> Result<Vec<u8>, Error> {
+    use flate2::read::MultiGzDecoder;
+
+    let mut out = Vec::new();
+    try!(MultiGzDecoder::new(input).take(max_len.saturating_add(1)).read_to_end(&mut out)
+        .map_err(|_| Error::from("failed to decompress gzip input")));
+    if out.len() as u64 > max_len {
+        return Err(Error::from("decompressed input is larger than the size limit"));
+    }
+    Ok(out)
+}
+
+#[cfg(not(feature = "compression"))]
+fn gunzip(_input: &[u8], _max_len: u64) -> Result<Vec<u8>, Error> {
+    Err(Error::from("gzip input needs the compression feature"))
+}
+
+/// Output of a decompressor that refuses writes past `max_len` bytes
+#[cfg(feature = "xz")]
+struct LimitedSink {
+    out: Vec<u8>,
+    max_len: u64,
+    exceeded: bool,
+}
+
+#[cfg(feature = "xz")]
+impl io::Write for LimitedSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.out.len() as u64 + buf.len() as u64 > self.max_len {
+            self.exceeded = true;
+            return Err(io::Error::new(io::ErrorKind::Other, "size limit exceeded"));
+        }
+        self.out.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "xz")]
+fn unxz(input: &[u8], max_len: u64) -> Result<Vec<u8>, Error> {
+    let mut sink = LimitedSink { out: Vec::new(), max_len: max_len, exceeded: false };
+    let result = lzma_rs::xz_decompress(&mut io::BufReader::new(input), &mut sink);
+    if sink.exceeded {
+        return Err(Error::from("decompressed input is larger than the size limit"));
+    }
+    try!(result.map_err(|_| Error::from("failed to decompress xz input")));
+    Ok(sink.out)
+}
+
+#[cfg(not(feature = "xz"))]
+fn unxz(_input: &[u8], _max_len: u64) -> Result<Vec<u8>, Error> {
+    Err(Error::from("xz input needs the xz feature"))
+}
+
+/// Most a deflate stream can expand its input (the format's limit is just
+/// over 1032:1)
+#[cfg(feature = "compression")]
+const MAX_INFLATE_RATIO: u64 = 1032;
+
+/// Inflate a zlib stream whose decompressed size is given by a header
+///
+/// The size comes from the file, so sizes no zlib stream of `compressed`'s
+/// length could reach are rejected before anything is decompressed, and
+/// output is never allocated up front.
+#[cfg(feature = "compression")]
+fn inflate(compressed: &[u8], size: u64) -> Result<Vec<u8>, Error> {
+    use flate2::read::ZlibDecoder;
+
+    if size > (compressed.len() as u64).saturating_mul(MAX_INFLATE_RATIO) {
+        return Err(Error::from("compressed section header claims an impossible size"));
+    }
+    let mut out = Vec::new();
+    try!(ZlibDecoder::new(compressed).take(size).read_to_end(&mut out)
+        .map_err(|_| Error::from("failed to decompress section")));
+    if (out.len() as u64) != size {
+        return Err(Error::from("decompressed section is smaller than its header claims"));
+    }
+    Ok(out)
+}
+
+fn parse_notes(data: types::Data, note_data: &[u8], align: u64) -> Result<Vec<types::Note>, Error> {
+    let mut notes = Vec::new();
+
+    let align = if align == 8 { 8 } else { 4 };
+    let pad = |x: u64| (x + align - 1) & !(align - 1);
+    let mut cur = io::Cursor::new(note_data);
+    while cur.position() + 12 <= note_data.len() as u64 {
+        let namesz = try!(read_u32!(data, cur)) as u64;
+        let descsz = try!(read_u32!(data, cur)) as u64;
+        let n_type = try!(read_u32!(data, cur));
+
+        let name_off = cur.position();
+        let desc_off = pad(name_off + namesz);
+        let end = desc_off + descsz;
+        if end > note_data.len() as u64 {
+            try!(Err(Error::Truncated { offset: name_off }));
+        }
+
+        let name = &note_data[name_off as usize..(name_off + namesz) as usize];
+        let name = match name.iter().position(|&x| x == 0) {
+            Some(nul) => &name[..nul],
+            None => name,
+        };
+        notes.push(types::Note {
+            name: String::from_utf8_lossy(name).into_owned(),
+            n_type: n_type,
+            desc: note_data[desc_off as usize..end as usize].to_vec(),
+        });
+        cur.set_position(pad(end));
+    }
+
+    Ok(notes)
+}
+
+/// Text in a fixed-size field, up to the first NUL
+fn fixed_string(field: &[u8]) -> String {
+    let end = field.iter().position(|&x| x == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+/// Decode an `NT_PRSTATUS` note; only the x86-64 register layout is known,
+/// other machines get `pr_reg` undecoded
+fn parse_prstatus(class: types::Class, data: types::Data, machine: types::Machine, desc: &[u8]) -> Result<types::PrStatus, Error> {
+    // Offsets of pr_pid and pr_reg, and the size of what follows pr_reg
+    // (pr_fpvalid and padding), which depend on the size of a long
+    let (ids, reg, tail) = match class {
+        types::ELFCLASS32 => (24, 72, 4),
+        _ => (32, 112, 8),
+    };
+    if desc.len() < reg + tail {
+        return Err(Error::from("prstatus note too short"));
+    }
+    let mut cur = io::Cursor::new(desc);
+    let signo = try!(read_u32!(data, cur));
+    cur.set_position(12);
+    let cursig = try!(read_u16!(data, cur));
+    cur.set_position(ids);
+    let pid = try!(read_u32!(data, cur));
+    let ppid = try!(read_u32!(data, cur));
+    let pgrp = try!(read_u32!(data, cur));
+    let sid = try!(read_u32!(data, cur));
+
+    let regs = &desc[reg..desc.len() - tail];
+    let registers = if machine == types::EM_X86_64 && regs.len() >= 27 * 8 {
+        let mut cur = io::Cursor::new(regs);
+        let mut r = [0u64; 27];
+        for x in r.iter_mut() {
+            *x = try!(read_u64!(data, cur));
+        }
+        types::Registers::X86_64(types::X86_64Registers {
+            r15: r[0], r14: r[1], r13: r[2], r12: r[3], rbp: r[4], rbx: r[5],
+            r11: r[6], r10: r[7], r9: r[8], r8: r[9], rax: r[10], rcx: r[11],
+            rdx: r[12], rsi: r[13], rdi: r[14], orig_rax: r[15], rip: r[16],
+            cs: r[17], eflags: r[18], rsp: r[19], ss: r[20], fs_base: r[21],
+            gs_base: r[22], ds: r[23], es: r[24], fs: r[25], gs: r[26],
+        })
+    } else {
+        types::Registers::Raw(regs.to_vec())
+    };
+
+    Ok(types::PrStatus {
+        signo: signo,
+        cursig: cursig,
+        pid: pid,
+        ppid: ppid,
+        pgrp: pgrp,
+        sid: sid,
+        registers: registers,
+    })
+}
+
+/// Decode a 64-bit `NT_PRPSINFO` note (32-bit machines disagree on the
+/// size of `uid_t`)
+fn parse_prpsinfo(data: types::Data, desc: &[u8]) -> Result<types::PrPsInfo, Error> {
+    if desc.len() < 136 {
+        return Err(Error::from("prpsinfo note too short"));
+    }
+    let mut cur = io::Cursor::new(desc);
+    cur.set_position(8);
+    let flag = try!(read_u64!(data, cur));
+    let uid = try!(read_u32!(data, cur));
+    let gid = try!(read_u32!(data, cur));
+    let pid = try!(read_u32!(data, cur));
+    let ppid = try!(read_u32!(data, cur));
+    let pgrp = try!(read_u32!(data, cur));
+    let sid = try!(read_u32!(data, cur));
+
+    Ok(types::PrPsInfo {
+        state: desc[0],
+        sname: desc[1] as char,
+        zombie: desc[2] != 0,
+        nice: desc[3] as i8,
+        flag: flag,
+        uid: uid,
+        gid: gid,
+        pid: pid,
+        ppid: ppid,
+        pgrp: pgrp,
+        sid: sid,
+        fname: fixed_string(&desc[40..56]),
+        psargs: fixed_string(&desc[56..136]),
+    })
+}
+
+/// Decode an `NT_AUXV` note, stopping at `AT_NULL`
+fn parse_auxv(class: types::Class, data: types::Data, desc: &[u8]) -> Result<Vec<(u64, u64)>, Error> {
+    let mut auxv = Vec::new();
+    let entsize = match class {
+        types::ELFCLASS32 => 8,
+        _ => 16,
+    };
+    let mut cur = io::Cursor::new(desc);
+    while cur.position() + entsize <= desc.len() as u64 {
+        let (key, value) = match class {
+            types::ELFCLASS32 => (try!(read_u32!(data, cur)) as u64, try!(read_u32!(data, cur)) as u64),
+            _ => (try!(read_u64!(data, cur)), try!(read_u64!(data, cur))),
+        };
+        if key == 0 {
+            break;
+        }
+        auxv.push((key, value));
+    }
+    Ok(auxv)
+}
+
+/// GOT slot that the PLT stub at `addr` jumps through
+///
+/// Finds the `jmp *disp(%rip)` of an x86-64 stub, or the `adrp`/`ldr`
+/// pair of an AArch64 one, allowing for a leading `endbr64`/`bti c`.
+fn plt_got_slot(machine: types::Machine, addr: u64, stub: &[u8]) -> Option<u64> {
+    let le32 = |b: &[u8]| (b[0] as u32) | (b[1] as u32) << 8 | (b[2] as u32) << 16 | (b[3] as u32) << 24;
+    match machine {
+        types::EM_X86_64 => {
+            (0..stub.len().saturating_sub(5))
+                .find(|&i| stub[i] == 0xff && stub[i + 1] == 0x25)
+                .map(|i| {
+                    let disp = le32(&stub[i + 2..i + 6]) as i32 as i64;
+                    addr.wrapping_add(i as u64 + 6).wrapping_add(disp as u64)
+                })
+        },
+        types::EM_AARCH64 => {
+            // A64 instructions are always little-endian
+            let insns: Vec<u32> = stub.chunks(4).filter(|w| w.len() == 4).map(|w| le32(w)).collect();
+            for i in 0..insns.len().saturating_sub(1) {
+                let (adrp, ldr) = (insns[i], insns[i + 1]);
+                if adrp & 0x9f000000 != 0x90000000 || ldr & 0xffc00000 != 0xf9400000 {
+                    continue;
+                }
+                let imm = ((adrp >> 5) & 0x7ffff) << 2 | (adrp >> 29) & 0x3;
+                // Sign-extend the 21-bit page offset
+                let imm = ((imm << 11) as i32 >> 11) as i64;
+                let page = (addr.wrapping_add(4 * i as u64) & !0xfff).wrapping_add((imm << 12) as u64);
+                return Some(page.wrapping_add((((ldr >> 10) & 0xfff) as u64) * 8));
+            }
+            None
+        },
+        _ => None,
+    }
+}
+
+/// Whether `section` lies within `segment`, by the rules `readelf -l` uses
+/// for its section to segment mapping
+fn section_in_segment(section: &Section, segment: &types::ProgramHeader) -> bool {
+    let tls = section.flags.contains(types::SHF_TLS);
+    let alloc = section.flags.contains(types::SHF_ALLOC);
+    let nobits = section.shtype == types::SHT_NOBITS;
+    let ptype = segment.progtype;
+
+    // .tbss takes up no room in any segment but PT_TLS
+    if nobits && tls && ptype != types::PT_TLS {
+        return false;
+    }
+    // Only PT_TLS, PT_LOAD and PT_GNU_RELRO hold TLS sections; PT_TLS holds
+    // nothing else, and PT_PHDR holds no sections at all
+    let type_ok = if tls {
+        ptype == types::PT_TLS || ptype == types::PT_LOAD || ptype == types::PT_GNU_RELRD
+    } else {
+        ptype != types::PT_TLS && ptype != types::PT_PHDR
+    };
+    if !type_ok {
+        return false;
+    }
+    // Segments that are mapped only hold allocated sections
+    if !alloc {
+        match ptype {
+            types::PT_LOAD | types::PT_DYNAMIC | types::PT_GNU_EH_FRAME |
+            types::PT_GNU_STACK | types::PT_GNU_RELRD => return false,
+            _ => {},
+        }
+    }
+    // Sections with file contents must lie within the segment's file image,
+    // and allocated ones within its memory image; a size that overflows
+    // lies within neither
+    if !nobits && !(section.offset >= segment.offset &&
+        section.offset - segment.offset <= segment.filesz.wrapping_sub(1) &&
+        (section.offset - segment.offset).checked_add(section.size).map_or(false, |end| end <= segment.filesz)) {
+        return false;
+    }
+    if alloc && !(section.addr >= segment.vaddr &&
+        section.addr - segment.vaddr <= segment.memsz.wrapping_sub(1) &&
+        (section.addr - segment.vaddr).checked_add(section.size).map_or(false, |end| end <= segment.memsz)) {
+        return false;
+    }
+    // Empty sections at the very start or end of PT_DYNAMIC/PT_NOTE don't count
+    if (ptype == types::PT_DYNAMIC || ptype == types::PT_NOTE) && section.size == 0 && segment.memsz != 0 {
+        let in_file = nobits || (section.offset > segment.offset && section.offset - segment.offset < segment.filesz);
+        let in_mem = !alloc || (section.addr > segment.vaddr && section.addr - segment.vaddr < segment.memsz);
+        return in_file && in_mem;
+    }
+    true
+}
+
+/// Cursor over `.eh_frame`/`.eh_frame_hdr` data mapped at `addr`, for
+/// decoding `DW_EH_PE_*` encoded values
+struct EhReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    addr: u64,
+    class: types::Class,
+    order: types::Data,
+}
+
+impl<'a> EhReader<'a> {
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], Error> {
+        match self.pos.checked_add(n) {
+            Some(end) if end <= self.data.len() => {
+                let b = &self.data[self.pos..end];
+                self.pos = end;
+                Ok(b)
+            },
+            _ => Err(Error::Truncated { offset: self.pos as u64 }),
+        }
+    }
+    fn uint(&mut self, n: usize) -> Result<u64, Error> {
+        let b = try!(self.bytes(n));
+        let mut x = 0u64;
+        for i in 0..n {
+            let byte = if self.order == types::ELFDATA2MSB { b[i] } else { b[n - 1 - i] };
+            x = x << 8 | byte as u64;
+        }
+        Ok(x)
+    }
+    fn u8(&mut self) -> Result<u8, Error> {
+        self.uint(1).map(|x| x as u8)
+    }
+    fn uleb(&mut self) -> Result<u64, Error> {
+        let mut x = 0u64;
+        let mut shift = 0;
+        loop {
+            let b = try!(self.u8());
+            if shift < 64 {
+                x |= ((b & 0x7f) as u64) << shift;
+            }
+            shift += 7;
+            if b & 0x80 == 0 {
+                return Ok(x);
+            }
+        }
+    }
+    fn sleb(&mut self) -> Result<i64, Error> {
+        let mut x = 0i64;
+        let mut shift = 0;
+        loop {
+            let b = try!(self.u8());
+            if shift < 64 {
+                x |= ((b & 0x7f) as i64) << shift;
+            }
+            shift += 7;
+            if b & 0x80 == 0 {
+                if shift < 64 && b & 0x40 != 0 {
+                    x |= -1i64 << shift;
+                }
+                return Ok(x);
+            }
+        }
+    }
+    fn cstr(&mut self) -> Result<&'a [u8], Error> {
+        match self.data[cmp::min(self.pos, self.data.len())..].iter().position(|&b| b == 0) {
+            Some(len) => {
+                let s = try!(self.bytes(len));
+                self.pos += 1;
+                Ok(s)
+            },
+            None => Err(Error::Truncated { offset: self.data.len() as u64 }),
+        }
+    }
+    /// Read a value in encoding `enc`, where `datarel` is the base for
+    /// `DW_EH_PE_DATAREL`; indirect values are returned as the address of
+    /// the pointer rather than followed
+    fn encoded(&mut self, enc: u8, datarel: u64) -> Result<u64, Error> {
+        let field = self.addr.wrapping_add(self.pos as u64);
+        let value = match enc & 0x0f {
+            types::DW_EH_PE_ABSPTR => match self.class {
+                types::ELFCLASS32 => try!(self.uint(4)),
+                _ => try!(self.uint(8)),
+            },
+            types::DW_EH_PE_ULEB128 => try!(self.uleb()),
+            types::DW_EH_PE_UDATA2 => try!(self.uint(2)),
+            types::DW_EH_PE_UDATA4 => try!(self.uint(4)),
+            types::DW_EH_PE_UDATA8 => try!(self.uint(8)),
+            types::DW_EH_PE_SLEB128 => try!(self.sleb()) as u64,
+            types::DW_EH_PE_SDATA2 => try!(self.uint(2)) as u16 as i16 as u64,
+            types::DW_EH_PE_SDATA4 => try!(self.uint(4)) as u32 as i32 as u64,
+            types::DW_EH_PE_SDATA8 => try!(self.uint(8)),
+            _ => return Err(Error::from("unsupported pointer encoding")),
+        };
+        let base = match enc & 0x70 {
+            types::DW_EH_PE_ABSPTR => 0,
+            types::DW_EH_PE_PCREL => field,
+            types::DW_EH_PE_DATAREL => datarel,
+            _ => return Err(Error::from("unsupported pointer encoding")),
+        };
+        Ok(base.wrapping_add(value))
+    }
+}
+
+/// Append the file-scope attributes of the `aeabi` subsection of an
+/// `.ARM.attributes` section to `out`
+///
+/// Integer-valued tags are ULEB128; `Tag_CPU_raw_name`, `Tag_CPU_name` and
+/// odd tags above 32 are NUL-terminated strings, and `Tag_compatibility`
+/// is one of each.
+fn parse_arm_attributes(class: types::Class, data: types::Data, attrs: &[u8], out: &mut Vec<(u32, types::AttrValue)>) -> Result<(), Error> {
+    if attrs.first() != Some(&b'A') {
+        return Err(Error::from("unknown attributes format version"));
+    }
+    let mut pos = 1;
+    while pos < attrs.len() {
+        let mut r = EhReader { data: &attrs[pos..], pos: 0, addr: 0, class: class, order: data };
+        let len = try!(r.uint(4)) as usize;
+        if len < 4 || len > r.data.len() {
+            return Err(Error::Truncated { offset: pos as u64 });
+        }
+        r.data = &r.data[..len];
+        if try!(r.cstr()) == b"aeabi" {
+            while r.pos < r.data.len() {
+                let start = r.pos;
+                let scope = try!(r.uleb());
+                let size = try!(r.uint(4)) as usize;
+                let end = match start.checked_add(size) {
+                    Some(end) if end <= r.data.len() && end >= r.pos => end,
+                    _ => return Err(Error::Truncated { offset: (pos + start) as u64 }),
+                };
+                if scope == types::TAG_FILE as u64 {
+                    let mut sub = EhReader { data: &r.data[..end], pos: r.pos, addr: 0, class: class, order: data };
+                    while sub.pos < end {
+                        let tag = try!(sub.uleb()) as u32;
+                        let value = match tag {
+                            types::TAG_COMPATIBILITY => {
+                                let flag = try!(sub.uleb());
+                                types::AttrValue::IntStr(flag, String::from_utf8_lossy(try!(sub.cstr())).into_owned())
+                            },
+                            types::TAG_CPU_RAW_NAME | types::TAG_CPU_NAME => {
+                                types::AttrValue::Str(String::from_utf8_lossy(try!(sub.cstr())).into_owned())
+                            },
+                            t if t > 32 && t & 1 == 1 => {
+                                types::AttrValue::Str(String::from_utf8_lossy(try!(sub.cstr())).into_owned())
+                            },
+                            _ => types::AttrValue::Int(try!(sub.uleb())),
+                        };
+                        out.push((tag, value));
+                    }
+                }
+                r.pos = end;
+            }
+        }
+        pos += len;
+    }
+    Ok(())
+}
+
+/// Read the initial location and address range of the `.eh_frame` FDE at
+/// `offset` into `eh_frame`, mapped at `addr`
+fn parse_fde(class: types::Class, data: types::Data, eh_frame: &[u8], addr: u64, offset: usize) -> Result<(u64, u64), Error> {
+    let mut r = EhReader { data: eh_frame, pos: offset, addr: addr, class: class, order: data };
+    let mut length = try!(r.uint(4));
+    let dwarf64 = length == 0xffffffff;
+    if dwarf64 {
+        length = try!(r.uint(8));
+    }
+    let id_pos = r.pos;
+    let cie_ptr = try!(r.uint(if dwarf64 { 8 } else { 4 }));
+    if length == 0 || cie_ptr == 0 || cie_ptr > id_pos as u64 {
+        return Err(Error::from("invalid FDE"));
+    }
+    let enc = try!(parse_cie_fde_encoding(class, data, eh_frame, addr, id_pos - cie_ptr as usize));
+    let begin = try!(r.encoded(enc, 0));
+    // The range is a plain length in the same format
+    let range = try!(r.encoded(enc & 0x0f, 0));
+    Ok((begin, range))
+}
+
+/// Pointer encoding the FDEs of the CIE at `offset` use (its `R` augmentation)
+fn parse_cie_fde_encoding(class: types::Class, data: types::Data, eh_frame: &[u8], addr: u64, offset: usize) -> Result<u8, Error> {
+    let mut r = EhReader { data: eh_frame, pos: offset, addr: addr, class: class, order: data };
+    let length = try!(r.uint(4));
+    let id = if length == 0xffffffff {
+        try!(r.uint(8));
+        try!(r.uint(8))
+    } else {
+        try!(r.uint(4))
+    };
+    if id != 0 {
+        return Err(Error::from("invalid CIE"));
+    }
+    let version = try!(r.u8());
+    let augmentation = try!(r.cstr());
+    if augmentation.windows(2).any(|w| w == b"eh") {
+        try!(r.encoded(types::DW_EH_PE_ABSPTR, 0));
+    }
+    try!(r.uleb());
+    try!(r.sleb());
+    if version == 1 {
+        try!(r.u8());
+    } else {
+        try!(r.uleb());
+    }
+
+    let mut enc = types::DW_EH_PE_ABSPTR;
+    if augmentation.first() == Some(&b'z') {
+        try!(r.uleb());
+        for &c in augmentation[1..].iter() {
+            match c {
+                b'L' => { try!(r.u8()); },
+                b'P' => {
+                    let penc = try!(r.u8());
+                    try!(r.encoded(penc & !types::DW_EH_PE_INDIRECT, 0));
+                },
+                b'R' => enc = try!(r.u8()),
+                b'S' | b'B' | b'G' => {},
+                _ => break,
+            }
+        }
+    }
+    Ok(enc)
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct File {
+    pub hdr: types::FileHeader,
+    pub segments: Vec<types::ProgramHeader>,
+    pub sections: Vec<Section>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub section_headers: Vec<types::SectionHeader>,
+    pub symbols: HashMap<String, u64>,
+    pub symbols_full: Vec<types::Symbol>,
+    pub dynamic_symbols: Vec<types::Symbol>,
+    pub relocations: HashMap<usize, Vec<types::Rela>>,
+    pub relocation_links: HashMap<usize, (u32, u32)>,
+    pub dynamic: Vec<types::DynEntry>,
+    pub notes: Vec<types::Note>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    dynamic_strtab: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    phoff: u64,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    shoff: u64,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    shstrndx: u32,
+    warnings: Vec<types::Warning>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    file_len: u64,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    arch_override: Option<::Arch>,
+}
+
+impl File {
+    pub fn parse<R: io::Read + io::Seek>(r: &mut R) -> Result<File, Error> {
+        File::parse_with(r, false, None)
+    }
+
+    /// Parse headers, symbols, relocations, dynamic entries and notes, but
+    /// defer reading the data of every other section
+    ///
+    /// The few small sections other accessors decode (`.eh_frame_hdr`,
+    /// `.gnu_debuglink`, `.gnu_debugaltlink`, `.interp`, `.ARM.attributes`
+    /// and the `.plt` stubs) are still read, so those accessors give the
+    /// same answers as after `parse`. Deferred sections have an empty
+    /// `data()` until `Section::load` is called on them with the same
+    /// reader.
+    pub fn parse_lazy<R: io::Read + io::Seek>(r: &mut R) -> Result<File, Error> {
+        File::parse_with(r, true, None)
+    }
+
+    /// `input` is the whole of `r` when the file is already in memory
+    #[allow(unused_variables,unused_assignments)]
+    fn parse_with<R: io::Read + io::Seek>(r: &mut R, lazy: bool, input: Option<&[u8]>) -> Result<File, Error> {
+        let mut headers = try!(header::parse(&mut IoSource(r)));
+        let hdr = headers.hdr;
+        let class = hdr.class;
+        let data = hdr.data;
+        let segments = headers.segments;
+        let sections_lst = headers.sections;
+        let shnum = sections_lst.len() as u64;
+        let shstrndx = headers.shstrndx;
+        let file_len = try!(r.seek(io::SeekFrom::End(0)));
+
+        let mut sections = Vec::new();
+
+        // Sections whose contents are needed to parse the rest of the file
+        let mut needed = vec![!lazy; shnum as usize];
+        if lazy {
+            for (i, section) in sections_lst.iter().enumerate() {
+                match section.shtype {
+                    types::SHT_SYMTAB | types::SHT_DYNSYM | types::SHT_DYNAMIC |
+                    types::SHT_GNU_VERNEED | types::SHT_GNU_VERDEF => {
+                        needed[i] = true;
+                        if (section.link as u64) < shnum {
+                            needed[section.link as usize] = true;
+                        }
+                    },
+                    types::SHT_REL | types::SHT_RELA | types::SHT_NOTE |
+                    types::SHT_GNU_VERSYM | types::SHT_GROUP => needed[i] = true,
+                    _ => needed[i] = needed_by_accessors(section),
+                }
+            }
+        }
+
+        let sections_data = match input {
+            #[cfg(feature = "rayon")]
+            Some(input) => try!(read_sections_par(&sections_lst, &needed, input)),
+            _ => try!(read_sections(r, &sections_lst, &needed, file_len)),
+        };
+
+        let mut notes = Vec::new();
+
+        for (i, section) in sections_lst.iter().enumerate() {
+            if section.shtype == types::SHT_NOTE {
+                notes.extend(try!(parse_notes(data, &sections_data[i], section.addralign)));
+            }
+        }
+
+        // Without a section header table the notes are only reachable
+        // through the program headers
+        if sections_lst.iter().all(|s| s.shtype != types::SHT_NOTE) {
+            for segment in segments.iter() {
+                if segment.progtype != types::PT_NOTE {
+                    continue;
+                }
+                match segment.offset.checked_add(segment.filesz) {
+                    Some(end) if end <= file_len => {}
+                    _ => try!(Err(Error::Truncated { offset: segment.offset })),
+                }
+                try!(r.seek(io::SeekFrom::Start(segment.offset)));
+                let mut note_data = vec![0u8; segment.filesz as usize];
+                try!(r.read_exact(&mut note_data));
+                notes.extend(try!(parse_notes(data, &note_data, segment.align)));
+            }
+        }
+
+        let mut symbols_full = Vec::new();
+        let mut dynamic_symbols = Vec::new();
+
+        for (i, section) in sections_lst.iter().enumerate() {
+            if section.shtype == types::SHT_SYMTAB {
+                symbols_full.extend(try!(parse_symbols(class, data, section, &sections_data[i], try!(linked_data(&sections_data, section)))));
+            } else if section.shtype == types::SHT_DYNSYM {
+                dynamic_symbols.extend(try!(parse_symbols(class, data, section, &sections_data[i], try!(linked_data(&sections_data, section)))));
+            }
+        }
+
+        let mut versions = HashMap::new();
+        for (i, section) in sections_lst.iter().enumerate() {
+            if section.shtype == types::SHT_GNU_VERNEED {
+                versions.extend(try!(parse_verneed(data, section, &sections_data[i], try!(linked_data(&sections_data, section)))));
+            } else if section.shtype == types::SHT_GNU_VERDEF {
+                versions.extend(try!(parse_verdef(data, section, &sections_data[i], try!(linked_data(&sections_data, section)))));
+            }
+        }
+        for (i, section) in sections_lst.iter().enumerate() {
+            if section.shtype == types::SHT_GNU_VERSYM {
+                try!(apply_versym(data, &sections_data[i], &versions, &mut dynamic_symbols, &mut headers.warnings));
+            }
+        }
+
+        let mut symbols = HashMap::new();
+        for sym in symbols_full.iter() {
+            symbols.insert(sym.name.clone(), sym.value);
+        }
+
+        let mut relocations = HashMap::new();
+        let mut relocation_links = HashMap::new();
+
+        for (i, section) in sections_lst.iter().enumerate() {
+            if section.shtype == types::SHT_REL || section.shtype == types::SHT_RELA {
+                let relocs = try!(parse_relocations(class, data, section, &sections_data[i]));
+                relocations.insert(i, relocs);
+                relocation_links.insert(i, (section.link, section.info));
+            }
+        }
+
+        let mut dynamic = Vec::new();
+        let mut dynamic_strtab = None;
+
+        for (i, section) in sections_lst.iter().enumerate() {
+            if section.shtype == types::SHT_DYNAMIC {
+                dynamic.extend(try!(parse_dynamic(class, data, &sections_data[i])));
+                dynamic_strtab = Some(section.link as usize);
+            }
+        }
+
+        for (i, (hdr, sect_data)) in sections_lst.iter().cloned().zip(sections_data.into_iter()).enumerate() {
+            #[cfg_attr(not(feature = "compression"), allow(unused_mut))]
+            let mut sect = Section {
+                name: hdr.name,
+                index: i,
+                addr: hdr.addr,
+                offset: hdr.offset,
+                size: hdr.size,
+                flags: hdr.flags,
+                shtype: hdr.shtype,
+                link: hdr.link,
+                info: hdr.info,
+                entsize: hdr.entsize,
+                addralign: hdr.addralign,
+                nobits: hdr.shtype == types::SHT_NOBITS,
+                compressed_size: None,
+                loaded: needed[i] || hdr.shtype == types::SHT_NOBITS || hdr.shtype == types::SHT_NULL,
+                data: sect_data,
+            };
+            // Compressed sections are only presented inflated, so are always read
+            let compressed = sect.flags.contains(types::SHF_COMPRESSED) || sect.name.starts_with(".zdebug_");
+            if !sect.loaded && compressed && cfg!(feature = "compression") {
+                try!(sect.load(r));
+            }
+            #[cfg(feature = "compression")]
+            {
+                if sect.flags.contains(types::SHF_COMPRESSED) && !sect.nobits {
+                    if let Some(inflated) = try!(decompress_section(class, data, &sect.data)) {
+                        sect.compressed_size = Some(sect.size);
+                        sect.size = inflated.len() as u64;
+                        sect.data = inflated;
+                    }
+                } else if sect.name.starts_with(".zdebug_") && !sect.nobits {
+                    let inflated = try!(decompress_zdebug(&sect.data));
+                    // Expose it under the canonical .debug_* name
+                    sect.name = format!(".debug_{}", &sect.name[".zdebug_".len()..]);
+                    sect.compressed_size = Some(sect.size);
+                    sect.size = inflated.len() as u64;
+                    sect.data = inflated;
+                }
+            }
+            sections.push(sect);
+        }
+
+        let x = File {
+            hdr: hdr,
+            segments: segments,
+            sections: sections,
+            section_headers: sections_lst,
+            symbols: symbols,
+            symbols_full: symbols_full,
+            dynamic_symbols: dynamic_symbols,
+            relocations: relocations,
+            relocation_links: relocation_links,
+            dynamic: dynamic,
+            notes: notes,
+            dynamic_strtab: dynamic_strtab,
+            phoff: headers.phoff,
+            shoff: headers.shoff,
+            shstrndx: shstrndx,
+            warnings: headers.warnings,
+            file_len: file_len,
+            arch_override: None,
+        };
+        Ok(x)
+    }
+
+    /// Read only the ELF header, leaving the rest of the file untouched
+    pub fn parse_header<R: io::Read + io::Seek>(r: &mut R) -> Result<types::FileHeader, Error> {
+        header::parse_header(&mut IoSource(r))
+    }
+
+    /// Parse an ELF file that is already in memory (e.g. a mmap'd file)
+    ///
+    /// Reads go through a cursor over `data`, so every access is bounds
+    /// checked against the slice rather than relying on a file's length.
+    pub fn parse_bytes(data: &[u8]) -> Result<File, Error> {
+        File::parse_with(&mut io::Cursor::new(data), false, Some(data))
+    }
+
+    /// Like `parse_bytes`, but without copying the contents of sections that
+    /// aren't needed for parsing; borrow them from `data` with
+    /// `section_data_in`, which checks that it is given the same buffer
+    pub fn parse_bytes_lazy(data: &[u8]) -> Result<File, Error> {
+        File::parse_with(&mut io::Cursor::new(data), true, Some(data))
+    }
+
+    /// Parse a file that may be stored gzip- or xz-compressed
+    ///
+    /// The whole input is read and, if it starts with the gzip or xz magic
+    /// number, decompressed into memory (parsing needs to seek, so it can't
+    /// be streamed); anything else is parsed as it is. Decoding gzip needs
+    /// the `compression` feature and xz the `xz` feature. Inputs, compressed
+    /// or not, larger than `DEFAULT_MAX_DECOMPRESSED_LEN` are rejected; see
+    /// `parse_compressed_limited`.
+    pub fn parse_compressed<R: io::Read>(r: R) -> Result<File, Error> {
+        File::parse_compressed_limited(r, DEFAULT_MAX_DECOMPRESSED_LEN)
+    }
+
+    /// Like `parse_compressed`, but failing once the input, or the result of
+    /// decompressing it, is more than `max_len` bytes
+    pub fn parse_compressed_limited<R: io::Read>(r: R, max_len: u64) -> Result<File, Error> {
+        let mut input = Vec::new();
+        try!(r.take(max_len.saturating_add(1)).read_to_end(&mut input));
+        if input.len() as u64 > max_len {
+            return Err(Error::from("input is larger than the size limit"));
+        }
+        if input.starts_with(&GZIP_MAGIC) {
+            input = try!(gunzip(&input, max_len));
+        } else if input.starts_with(&XZ_MAGIC) {
+            input = try!(unxz(&input, max_len));
+        }
+        File::parse_bytes(&input)
+    }
+
+    /// Write the file back out with its headers and section contents
+    ///
+    /// `r` must be the input this file was parsed from. Its bytes are copied
+    /// as they are, so anything not described by a header (padding, trailing
+    /// data, `e_ident` bytes the parser doesn't interpret) is kept, and then
+    /// the ELF, program and section headers and every loaded section are
+    /// written over them. Only size-preserving edits are supported for now:
+    /// each section is written at its original offset, so it must keep its
+    /// size (see `Section::set_data`) and not have been decompressed.
+    /// Sections that were never loaded (see `parse_lazy`) are left as read.
+    pub fn write<R: io::Read + io::Seek, W: io::Write + io::Seek>(&self, r: &mut R, w: &mut W) -> Result<(), Error> {
+        let class = self.hdr.class;
+        let data = self.hdr.data;
+        let (ehsize, phentsize, shentsize) = match class {
+            types::ELFCLASS32 => (52u16, 32u16, 40u16),
+            _ => (64u16, 56u16, 64u16),
+        };
+
+        for section in self.sections.iter() {
+            if section.nobits || section.shtype == types::SHT_NULL || !section.loaded {
+                continue;
+            }
+            if section.compressed_size.is_some() {
+                return Err(Error::Other(format!("section {} was decompressed and cannot be written back", section.name)));
+            }
+            if section.data.len() as u64 != section.size {
+                return Err(Error::Other(format!("section {} changed size", section.name)));
+            }
+        }
+
+        let mut original = Vec::new();
+        try!(r.seek(io::SeekFrom::Start(0)));
+        try!(io::Read::by_ref(r).take(self.file_len).read_to_end(&mut original));
+        if (original.len() as u64) < self.file_len {
+            return Err(Error::Truncated { offset: original.len() as u64 });
+        }
+
+        let shstrtab = self.sections.get(self.shstrndx as usize).and_then(|s| s.data_in(&original));
+        let mut name_idxs = Vec::new();
+        for section in self.sections.iter() {
+            let idx = match shstrtab {
+                Some(table) => match find_elf_string(table, &section.name) {
+                    Some(idx) => idx as u32,
+                    None => return Err(Error::Other(format!("section name {} is missing from the string table", section.name))),
+                },
+                None => 0,
+            };
+            name_idxs.push(idx);
+        }
+
+        let mut out = io::Cursor::new(original);
+        for section in self.sections.iter() {
+            if section.nobits || section.shtype == types::SHT_NULL || !section.loaded {
+                continue;
+            }
+            try!(out.seek(io::SeekFrom::Start(section.offset)));
+            try!(out.write_all(&section.data));
+        }
+
+        // Counts and indexes that don't fit in the header stay in section 0
+        let shnum = self.sections.len();
+        let phnum = self.segments.len();
+        try!(out.seek(io::SeekFrom::Start(types::EI_OSABI as u64)));
+        try!(out.write_all(&[self.hdr.os_abi.0, self.hdr.abi_version]));
+        try!(out.seek(io::SeekFrom::Start(types::EI_NIDENT as u64)));
+        try!(write_u16!(data, out, self.hdr.elf_type.0));
+        try!(write_u16!(data, out, self.hdr.machine.0));
+        try!(write_u32!(data, out, self.hdr.version.0));
+        try!(write_word!(class, data, out, self.hdr.entrypoint));
+        try!(write_word!(class, data, out, if phnum == 0 { 0 } else { self.phoff }));
+        try!(write_word!(class, data, out, if shnum == 0 { 0 } else { self.shoff }));
+        try!(write_u32!(data, out, self.hdr.flags));
+        try!(write_u16!(data, out, ehsize));
+        try!(write_u16!(data, out, if phnum == 0 { 0 } else { phentsize }));
+        try!(write_u16!(data, out, cmp::min(phnum, types::PN_XNUM as usize) as u16));
+        try!(write_u16!(data, out, shentsize));
+        try!(write_u16!(data, out, if shnum >= types::SHN_LORESERVE as usize { 0 } else { shnum as u16 }));
+        try!(write_u16!(data, out, if self.shstrndx >= types::SHN_LORESERVE as u32 { types::SHN_XINDEX } else { self.shstrndx as u16 }));
+
+        try!(out.seek(io::SeekFrom::Start(self.phoff)));
+        for segment in self.segments.iter() {
+            try!(write_u32!(data, out, segment.progtype.0));
+            match class {
+                types::ELFCLASS32 => {
+                    try!(write_u32!(data, out, segment.offset as u32));
+                    try!(write_u32!(data, out, segment.vaddr as u32));
+                    try!(write_u32!(data, out, segment.paddr as u32));
+                    try!(write_u32!(data, out, segment.filesz as u32));
+                    try!(write_u32!(data, out, segment.memsz as u32));
+                    try!(write_u32!(data, out, segment.flags.0));
+                    try!(write_u32!(data, out, segment.align as u32));
+                }
+                _ => {
+                    try!(write_u32!(data, out, segment.flags.0));
+                    try!(write_u64!(data, out, segment.offset));
+                    try!(write_u64!(data, out, segment.vaddr));
+                    try!(write_u64!(data, out, segment.paddr));
+                    try!(write_u64!(data, out, segment.filesz));
+                    try!(write_u64!(data, out, segment.memsz));
+                    try!(write_u64!(data, out, segment.align));
+                }
+            }
+        }
+
+        if shnum != 0 {
+            try!(out.seek(io::SeekFrom::Start(self.shoff)));
+        }
+        for (section, name_idx) in self.sections.iter().zip(name_idxs.into_iter()) {
+            try!(write_u32!(data, out, name_idx));
+            try!(write_u32!(data, out, section.shtype.0));
+            try!(write_word!(class, data, out, section.flags.0));
+            try!(write_word!(class, data, out, section.addr));
+            try!(write_word!(class, data, out, section.offset));
+            try!(write_word!(class, data, out, section.size));
+            try!(write_u32!(data, out, section.link));
+            try!(write_u32!(data, out, section.info));
+            try!(write_word!(class, data, out, section.addralign));
+            try!(write_word!(class, data, out, section.entsize));
+        }
+
+        try!(w.seek(io::SeekFrom::Start(0)));
+        try!(w.write_all(out.get_ref()));
+        Ok(())
+    }
+
+    pub fn segments(&self) -> &[types::ProgramHeader] {
+        &self.segments
+    }
+    /// Section headers as read from the file, before any decompression or
+    /// renaming of `.zdebug_*` sections, in the same order as `sections()`
+    pub fn section_headers(&self) -> &[types::SectionHeader] {
+        &self.section_headers
+    }
+    /// Check that every `PT_LOAD` segment can be mapped as described: its
+    /// file data lies within the file, is no larger than its memory image,
+    /// and its address and offset agree modulo a power-of-two alignment
+    ///
+    /// All violations are returned, in program header order.
+    pub fn verify_loadable(&self) -> Result<(), Vec<types::LoadError>> {
+        let mut errors = Vec::new();
+        for (i, seg) in self.segments.iter().enumerate() {
+            if seg.progtype != types::PT_LOAD {
+                continue;
+            }
+            match seg.offset.checked_add(seg.filesz) {
+                Some(end) if end <= self.file_len => {},
+                end => errors.push(types::LoadError::Truncated { segment: i, end: end.unwrap_or(u64::max_value()), file_len: self.file_len }),
+            }
+            if seg.filesz > seg.memsz {
+                errors.push(types::LoadError::FileszExceedsMemsz { segment: i, filesz: seg.filesz, memsz: seg.memsz });
+            }
+            if seg.align > 1 {
+                if !seg.align.is_power_of_two() {
+                    errors.push(types::LoadError::BadAlign { segment: i, align: seg.align });
+                } else if seg.vaddr % seg.align != seg.offset % seg.align {
+                    errors.push(types::LoadError::Misaligned { segment: i, vaddr: seg.vaddr, offset: seg.offset, align: seg.align });
+                }
+            }
+        }
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+    /// Problems noticed while parsing that weren't severe enough to fail it
+    pub fn warnings(&self) -> &[types::Warning] {
+        &self.warnings
+    }
+    pub fn sections(&self) -> &[Section] {
+        &self.sections
+    }
+    /// Sections in section header table order
+    pub fn iter_sections(&self) -> impl Iterator<Item = &Section> {
+        self.sections.iter()
+    }
+    pub fn section_at(&self, index: usize) -> Option<&Section> {
+        self.sections.get(index)
+    }
+    /// `section.data_in(input)`, after checking that `input` is as long as
+    /// the file this was parsed from, so a buffer holding some other file
+    /// is rejected rather than sliced at this file's offsets
+    pub fn section_data_in<'a>(&self, section: &'a Section, input: &'a [u8]) -> Option<&'a [u8]> {
+        if input.len() as u64 != self.file_len {
+            return None;
+        }
+        section.data_in(input)
+    }
+    /// Make `arch()` report `arch` regardless of `e_machine`, for machines
+    /// that `Arch` doesn't know (where it would otherwise be `Arch::Unknown`)
+    pub fn set_arch_override(&mut self, arch: ::Arch) {
+        self.arch_override = Some(arch);
+    }
+    pub fn symbols(&self) -> &HashMap<String, u64> {
+        &self.symbols
+    }
+    pub fn symbols_full(&self) -> &[types::Symbol] {
+        &self.symbols_full
+    }
+    /// `.symtab` symbols in symbol table order, for streaming over them
+    /// without going through the `symbols()` map
+    pub fn iter_symbols(&self) -> impl Iterator<Item = &types::Symbol> {
+        self.symbols_full.iter()
+    }
+    pub fn dynamic_symbols(&self) -> &[types::Symbol] {
+        &self.dynamic_symbols
+    }
+    /// Map of raw symbol names (static and dynamic) to their demangled form
+    #[cfg(feature = "demangle")]
+    pub fn demangle_symbols(&self) -> HashMap<String, String> {
+        self.symbols_full.iter().chain(self.dynamic_symbols.iter())
+            .map(|s| (s.name.clone(), s.demangled()))
+            .collect()
+    }
+    /// Static and dynamic symbols matching `query`, in table order
+    ///
+    /// A plain name matches every version of the symbol, by its raw name
+    /// or (with the `demangle` feature) its demangled one. A query with a
+    /// version, `name@VERSION` or `name@@VERSION`, only matches that version;
+    /// `@` and `@@` are treated alike. Matching is case-sensitive.
+    pub fn find_symbol(&self, query: &str) -> Vec<&types::Symbol> {
+        let (name, version) = match query.find('@') {
+            Some(at) => (&query[..at], Some(query[at..].trim_start_matches('@'))),
+            None => (query, None),
+        };
+        self.symbols_full.iter().chain(self.dynamic_symbols.iter()).filter(|s| {
+            match version {
+                Some(v) => s.name == name && s.version.as_ref().map_or(false, |sv| sv == v),
+                None => s.name == name || demangled_is(s, name),
+            }
+        }).collect()
+    }
+    /// Relocation entries keyed by the index of the SHT_REL/SHT_RELA section
+    /// they came from (SHT_REL entries carry an addend of 0)
+    pub fn relocations(&self) -> &HashMap<usize, Vec<types::Rela>> {
+        &self.relocations
+    }
+    /// Relocation entries of the first SHT_REL/SHT_RELA section called `name`
+    pub fn relocations_named(&self, name: &str) -> Option<&[types::Rela]> {
+        self.sections.iter()
+            .filter(|s| s.name == name)
+            .filter_map(|s| self.relocations.get(&s.index))
+            .next()
+            .map(|r| &r[..])
+    }
+    /// `(sh_link, sh_info)` of each relocation section, keyed by its index,
+    /// i.e. the index of the symbol table it uses and of the section it
+    /// applies to
+    pub fn relocation_links(&self) -> &HashMap<usize, (u32, u32)> {
+        &self.relocation_links
+    }
+    /// Every relocation, in section order, with its type named and its
+    /// symbol looked up in the symbol table the section's `sh_link` names
+    pub fn resolved_relocations(&self) -> Vec<types::ResolvedReloc> {
+        let mut resolved = Vec::new();
+        for (i, section) in self.sections.iter().enumerate() {
+            let (relocs, &(link, _)) = match (self.relocations.get(&i), self.relocation_links.get(&i)) {
+                (Some(r), Some(l)) => (r, l),
+                _ => continue,
+            };
+            let symtab = match self.sections.get(link as usize).map(|s| s.shtype) {
+                Some(types::SHT_DYNSYM) => &self.dynamic_symbols[..],
+                Some(types::SHT_SYMTAB) => &self.symbols_full[..],
+                _ => &[],
+            };
+            for rel in relocs.iter() {
+                let symbol = match symtab.get(rel.sym as usize) {
+                    _ if rel.sym == 0 => None,
+                    Some(sym) if sym.sym_type == types::STT_SECTION => {
+                        self.sections.get(sym.shndx as usize).map(|s| s.name.clone())
+                    },
+                    Some(sym) => Some(sym.name.clone()),
+                    None => None,
+                };
+                resolved.push(types::ResolvedReloc {
+                    section: section.name.clone(),
+                    offset: rel.offset,
+                    rel_type: match types::reloc_type_name(self.hdr.machine, rel.rel_type) {
+                        Some(name) => String::from(name),
+                        None => format!("{:#x}", rel.rel_type),
+                    },
+                    symbol: symbol,
+                    addend: rel.addend,
+                });
+            }
+        }
+        resolved
     }
-
-    let mut ret = String::with_capacity(end - start);
-    for i in start..end {
-        ret.push(data[i] as char);
+    pub fn dynamic(&self) -> &[types::DynEntry] {
+        &self.dynamic
     }
-
-    ret
-}
-
-pub struct File {
-    pub hdr: types::FileHeader,
-    pub sections: HashMap<String, Section>,
-    pub symbols: HashMap<String, u64>,
-}
-
-impl File {
-    #[allow(unused_variables,unused_assignments)]
-    pub fn parse<R: io::Read + io::Seek>(r: &mut R) -> Result<File, Box<error::Error>> {
-        try!(r.seek(io::SeekFrom::Start(0)));
-        let mut eident = [0u8; types::EI_NIDENT];
-        try!(r.read(&mut eident));
-
-        if eident[0..4] != types::ELFMAG {
-            try!(Err(Error::from("invalid magic number")));
+    /// Resolve a string-valued dynamic entry (`DT_NEEDED`, `DT_SONAME`,
+    /// `DT_RPATH`, `DT_RUNPATH`) against the dynamic string table
+    pub fn dynamic_str(&self, entry: &types::DynEntry) -> Option<String> {
+        match entry.tag {
+            types::DT_NEEDED | types::DT_SONAME | types::DT_RPATH | types::DT_RUNPATH => {}
+            _ => return None,
         }
+        let strtab = match self.dynamic_strtab {
+            Some(idx) => self.section_at(idx),
+            None => None,
+        };
+        strtab.and_then(|s| header::table_string(&s.data, entry.value as usize).ok())
+    }
+    /// Names of the shared objects listed as `DT_NEEDED`, in declaration order
+    pub fn needed_libraries(&self) -> Vec<String> {
+        self.dynamic.iter()
+            .filter(|e| e.tag == types::DT_NEEDED)
+            .filter_map(|e| self.dynamic_str(e))
+            .collect()
+    }
+    /// `DT_SONAME` of a shared object, or `None` if there is no such entry
+    pub fn soname(&self) -> Option<String> {
+        self.dynamic.iter()
+            .find(|e| e.tag == types::DT_SONAME)
+            .and_then(|e| self.dynamic_str(e))
+    }
+    /// Entries of the `DT_RPATH` search path, unexpanded (`$ORIGIN` etc. are
+    /// left as is); an empty entry, which the loader takes as the current
+    /// directory, is kept as an empty string
+    pub fn rpath(&self) -> Vec<String> {
+        self.search_path(types::DT_RPATH)
+    }
+    /// Entries of the `DT_RUNPATH` search path, as for `rpath`
+    pub fn runpath(&self) -> Vec<String> {
+        self.search_path(types::DT_RUNPATH)
+    }
+    fn search_path(&self, tag: types::DynTag) -> Vec<String> {
+        self.dynamic.iter()
+            .filter(|e| e.tag == tag)
+            .filter_map(|e| self.dynamic_str(e))
+            .flat_map(|s| s.split(':').map(String::from).collect::<Vec<_>>())
+            .collect()
+    }
+    /// Every note from the SHT_NOTE sections, or from the PT_NOTE segments
+    /// if the file has no note sections
+    pub fn notes(&self) -> &[types::Note] {
+        &self.notes
+    }
+    /// GNU build-id (`NT_GNU_BUILD_ID` note), as raw bytes
+    pub fn build_id(&self) -> Option<Vec<u8>> {
+        self.notes.iter()
+            .find(|n| n.name == "GNU" && n.n_type == types::NT_GNU_BUILD_ID)
+            .map(|n| n.desc.clone())
+    }
+    /// Thread registers, process information and auxiliary vector of a core
+    /// dump, from its `CORE` notes; `None` if this isn't an `ET_CORE` file
+    ///
+    /// Notes that are too short to decode are skipped.
+    pub fn core_info(&self) -> Option<types::CoreInfo> {
+        if self.hdr.elf_type != types::ET_CORE {
+            return None;
+        }
+        let (class, data) = (self.hdr.class, self.hdr.data);
+        let mut info = types::CoreInfo {
+            threads: Vec::new(),
+            process: None,
+            auxv: Vec::new(),
+        };
+        for note in self.notes.iter().filter(|n| n.name == "CORE") {
+            match note.n_type {
+                types::NT_PRSTATUS => {
+                    if let Ok(status) = parse_prstatus(class, data, self.hdr.machine, &note.desc) {
+                        info.threads.push(status);
+                    }
+                },
+                types::NT_PRPSINFO if class == types::ELFCLASS64 => {
+                    info.process = parse_prpsinfo(data, &note.desc).ok();
+                },
+                types::NT_AUXV => {
+                    if let Ok(auxv) = parse_auxv(class, data, &note.desc) {
+                        info.auxv = auxv;
+                    }
+                },
+                _ => {},
+            }
+        }
+        Some(info)
+    }
+    /// GNU build-id formatted as a lowercase hex string
+    pub fn build_id_hex(&self) -> Option<String> {
+        self.build_id().map(|id| id.iter().map(|b| format!("{:02x}", b)).collect())
+    }
+    /// File name and CRC32 of the separate debug file, from `.gnu_debuglink`
+    ///
+    /// The name is NUL-terminated and padded to a multiple of 4 bytes, and
+    /// the CRC follows in the file's byte order.
+    pub fn debuglink(&self) -> Option<(String, u32)> {
+        let data = match self.section_data(".gnu_debuglink") {
+            Some(d) => d,
+            None => return None,
+        };
+        let nul = match data.iter().position(|x| *x == 0) {
+            Some(n) => n,
+            None => return None,
+        };
+        let crc_off = (nul + 4) & !3;
+        if crc_off + 4 > data.len() {
+            return None;
+        }
+        let b = &data[crc_off..crc_off + 4];
+        let crc = match self.hdr.data {
+            types::ELFDATA2MSB => (b[0] as u32) << 24 | (b[1] as u32) << 16 | (b[2] as u32) << 8 | b[3] as u32,
+            _ => b[0] as u32 | (b[1] as u32) << 8 | (b[2] as u32) << 16 | (b[3] as u32) << 24,
+        };
+        Some((String::from_utf8_lossy(&data[..nul]).into_owned(), crc))
+    }
+    /// File name and build-id of the supplementary debug file shared between
+    /// several objects (as made by `dwz`), from `.gnu_debugaltlink`
+    pub fn debugaltlink(&self) -> Option<(String, Vec<u8>)> {
+        let data = match self.section_data(".gnu_debugaltlink") {
+            Some(d) => d,
+            None => return None,
+        };
+        data.iter().position(|x| *x == 0)
+            .map(|nul| (String::from_utf8_lossy(&data[..nul]).into_owned(), data[nul + 1..].to_vec()))
+    }
+    /// Allocated section whose `[addr, addr + size)` range contains `addr`
+    pub fn section_for_addr(&self, addr: u64) -> Option<&Section> {
+        self.sections.iter().find(|s| {
+            s.is_alloc() && s.addr != 0 && addr >= s.addr && addr - s.addr < s.size
+        })
+    }
+    /// Contents of the first section called `name`
+    pub fn section_data(&self, name: &str) -> Option<&[u8]> {
+        self.get_section(name).map(|s| s.data())
+    }
+    /// Read the contents of the first section called `name` straight from
+    /// `r`, without keeping them in the file
+    ///
+    /// `r` must be the same input this file was parsed from: only the
+    /// section's offset and size are known here, so any other reader just
+    /// yields whatever bytes sit at that position. Pair this with
+    /// `parse_lazy` to look at one section of a large file at a time. The
+    /// bytes are as stored, so a compressed section comes back compressed,
+    /// and an `SHT_NOBITS` section comes back empty.
+    pub fn read_section_data<R: io::Read + io::Seek>(&self, r: &mut R, name: &str) -> Result<Vec<u8>, Error> {
+        let hdr = match self.get_section(name).and_then(|s| self.section_headers.get(s.index)) {
+            Some(hdr) => hdr,
+            None => return Err(Error::Other(format!("no section named {}", name))),
+        };
+        if hdr.shtype == types::SHT_NOBITS {
+            return Ok(Vec::new());
+        }
+        let file_len = try!(r.seek(io::SeekFrom::End(0)));
+        if hdr.offset.checked_add(hdr.size).map_or(true, |end| end > file_len) {
+            return Err(Error::Truncated { offset: hdr.offset });
+        }
+        try!(r.seek(io::SeekFrom::Start(hdr.offset)));
+        let mut data = vec![0u8; hdr.size as usize];
+        try!(r.read_exact(&mut data));
+        Ok(data)
+    }
+    /// The `len` bytes at virtual address `addr`, if they all lie within the
+    /// loaded data of a single allocated section
+    pub fn read_at(&self, addr: u64, len: usize) -> Option<&[u8]> {
+        let section = match self.section_for_addr(addr) {
+            Some(s) if !s.nobits => s,
+            _ => return None,
+        };
+        let start = (addr - section.addr) as usize;
+        match start.checked_add(len) {
+            Some(end) if end <= section.data.len() => Some(&section.data[start..end]),
+            _ => None,
+        }
+    }
+    /// Memory image of the `PT_LOAD` segments as a loader would map them,
+    /// as `(address, bytes)` regions in address order
+    ///
+    /// Each segment's `p_filesz` bytes are placed at `p_vaddr`, then
+    /// zero-filled up to `p_memsz`. Regions are widened to 4 KiB page
+    /// boundaries with zeros, and segments that share or touch a page are
+    /// merged into one region, so every region can be mapped as it is.
+    ///
+    /// The bytes are read from `r`, which must be the input this file was
+    /// parsed from: the parsed file only keeps section contents, while a
+    /// segment also covers bytes outside any section (the ELF and program
+    /// headers, padding), and those may not have been read at all after
+    /// `parse_lazy`. Since `p_memsz` can be far larger than the file, the
+    /// regions' total size is checked against `limit` before anything is
+    /// allocated, and an image that would exceed it is an error.
+    pub fn memory_image<R: io::Read + io::Seek>(&self, r: &mut R, limit: u64) -> Result<Vec<(u64, Vec<u8>)>, Error> {
+        const PAGE_SIZE: u64 = 0x1000;
 
-        let class = types::Class(eident[types::EI_CLASS]);
-        let data = types::Data(eident[types::EI_DATA]);
-        let os_abi = types::OsAbi(eident[types::EI_OSABI]);
-        let abi_version = eident[types::EI_ABIVERSION];
-
-        let elf_type = types::Type(try!(read_u16!(data, r)));
-        let machine = types::Machine(try!(read_u16!(data, r)));
-        let version = types::Version(try!(read_u32!(data, r)));
+        let mut loads: Vec<&types::ProgramHeader> = self.segments.iter()
+            .filter(|s| s.progtype == types::PT_LOAD && s.memsz > 0)
+            .collect();
+        loads.sort_by_key(|s| s.vaddr);
 
-        let entry: u64;
-        let phoff: u64;
-        let shoff: u64;
+        // Work out the page-aligned regions and each segment's region first
+        let file_len = try!(r.seek(io::SeekFrom::End(0)));
+        let mut extents: Vec<(u64, u64)> = Vec::new();
+        let mut region_of = Vec::with_capacity(loads.len());
+        for seg in loads.iter() {
+            let start = seg.vaddr & !(PAGE_SIZE - 1);
+            let end = match seg.vaddr.checked_add(seg.memsz).and_then(|e| e.checked_add(PAGE_SIZE - 1)) {
+                Some(end) => end & !(PAGE_SIZE - 1),
+                None => return Err(Error::Other(format!("segment at {:#x} wraps the address space", seg.vaddr))),
+            };
+            if seg.offset.checked_add(cmp::min(seg.filesz, seg.memsz)).map_or(true, |end| end > file_len) {
+                return Err(Error::Truncated { offset: seg.offset });
+            }
+            match extents.last_mut() {
+                Some(last) if start <= last.1 => last.1 = cmp::max(last.1, end),
+                _ => extents.push((start, end)),
+            }
+            region_of.push(extents.len() - 1);
+        }
+        let total = extents.iter().fold(0u64, |n, &(start, end)| n.saturating_add(end - start));
+        if total > limit {
+            return Err(Error::Other(format!("memory image of {:#x} bytes exceeds the limit of {:#x}", total, limit)));
+        }
 
-        match class {
-            types::ELFCLASS32 => {
-                entry = try!(read_u32!(data, r)) as u64;
-                phoff = try!(read_u32!(data, r)) as u64;
-                shoff = try!(read_u32!(data, r)) as u64;
+        let mut image: Vec<(u64, Vec<u8>)> = extents.iter()
+            .map(|&(start, end)| (start, vec![0u8; (end - start) as usize]))
+            .collect();
+        for (seg, &i) in loads.iter().zip(region_of.iter()) {
+            let region = &mut image[i];
+            let at = (seg.vaddr - region.0) as usize;
+            let filesz = cmp::min(seg.filesz, seg.memsz) as usize;
+            try!(r.seek(io::SeekFrom::Start(seg.offset)));
+            try!(r.read_exact(&mut region.1[at..at + filesz]));
+            // An earlier segment may have put file data in our zero-fill
+            for b in region.1[at + filesz..at + seg.memsz as usize].iter_mut() {
+                *b = 0;
             }
-            types::ELFCLASS64 => {
-                entry = try!(read_u64!(data, r));
-                phoff = try!(read_u64!(data, r));
-                shoff = try!(read_u64!(data, r));
+        }
+        Ok(image)
+    }
+    /// SHA-256 of each named section's contents, keyed by section name
+    ///
+    /// Hashes cover `data()`, and zero-fill sections hash as empty. Sections
+    /// deferred by `parse_lazy` and not loaded since are left out rather
+    /// than hashed as if they were empty.
+    #[cfg(feature = "hashing")]
+    pub fn section_hashes(&self) -> HashMap<String, [u8; 32]> {
+        self.sections.iter()
+            .filter(|s| !s.name.is_empty() && (s.loaded || s.nobits))
+            .map(|s| (s.name.clone(), s.sha256()))
+            .collect()
+    }
+    /// Source file and line of the instruction at `addr`, from `.debug_line`
+    ///
+    /// The file is joined onto its include directory when it is relative.
+    /// `None` if there is no DWARF line information covering `addr`, which
+    /// includes files from `parse_lazy` whose `.debug_*` sections haven't
+    /// been loaded (see `Section::load`).
+    #[cfg(feature = "dwarf")]
+    pub fn addr_to_line(&self, addr: u64) -> Option<(String, u32)> {
+        self.dwarf_line(addr).unwrap_or(None)
+    }
+    #[cfg(feature = "dwarf")]
+    fn dwarf_line(&self, addr: u64) -> Result<Option<(String, u32)>, gimli::Error> {
+        let endian = match self.hdr.data {
+            types::ELFDATA2MSB => gimli::RunTimeEndian::Big,
+            _ => gimli::RunTimeEndian::Little,
+        };
+        let dwarf = try!(gimli::Dwarf::load(|id: gimli::SectionId| -> Result<_, gimli::Error> {
+            Ok(gimli::EndianSlice::new(self.section_data(id.name()).unwrap_or(&[]), endian))
+        }));
+
+        let mut units = dwarf.units();
+        while let Some(header) = try!(units.next()) {
+            let unit = try!(dwarf.unit(header));
+            let program = match unit.line_program {
+                Some(ref program) => program.clone(),
+                None => continue,
+            };
+            // Each row covers the addresses up to the next one in its sequence
+            let mut rows = program.rows();
+            let mut prev: Option<(u64, u64, u32)> = None;
+            while let Some((header, row)) = try!(rows.next_row()) {
+                if let Some((start, file, line)) = prev {
+                    if addr >= start && addr < row.address() {
+                        let entry = match header.file(file) {
+                            Some(entry) => entry,
+                            None => return Ok(None),
+                        };
+                        let mut path = String::from(try!(dwarf.attr_string(&unit, entry.path_name())).to_string_lossy());
+                        if !path.starts_with('/') {
+                            if let Some(dir) = entry.directory(header) {
+                                let dir = try!(dwarf.attr_string(&unit, dir));
+                                path = format!("{}/{}", dir.to_string_lossy(), path);
+                            }
+                        }
+                        return Ok(Some((path, line)));
+                    }
+                }
+                prev = if row.end_sequence() {
+                    None
+                } else {
+                    Some((row.address(), row.file_index(), row.line().map_or(0, |l| l.get() as u32)))
+                };
             }
-            _ => return Err(Box::new(Error::from("invalid class"))),
         }
+        Ok(None)
+    }
+    /// Disassemble the first section called `name`, as loaded at its `addr()`
+    ///
+    /// In 32-bit ARM files the `$a`/`$t`/`$d` mapping symbols split the
+    /// section into ARM code, Thumb code and data, which are disassembled in
+    /// the matching mode or skipped. Code before the first mapping symbol
+    /// (or in a file without them) uses the mode `arch()` reports.
+    ///
+    /// A section deferred by `parse_lazy` is an error until it is loaded
+    /// with `Section::load`.
+    #[cfg(feature = "disasm")]
+    pub fn disassemble_section(&self, name: &str) -> Result<Vec<::disasm::Insn>, Error> {
+        let section = match self.get_section(name) {
+            Some(s) => s,
+            None => return Err(Error::from("no such section")),
+        };
+        if !section.loaded && !section.nobits {
+            return Err(Error::Other(format!("section {} has not been loaded", section.name)));
+        }
+        let (endian, default_mode, kind) = match self.arch() {
+            ::Arch::ARM(::Width::W32, endian, mode, kind) => (endian, mode, kind),
+            arch => return ::disasm::disassemble(arch, &section.data, section.addr),
+        };
 
-        let flags = try!(read_u32!(data, r));
-        let ehsize = try!(read_u16!(data, r));
-        let phentsize = try!(read_u16!(data, r));
-        let phnum = try!(read_u16!(data, r));
-        let shentsize = try!(read_u16!(data, r));
-        let shnum = try!(read_u16!(data, r));
-        let shstrndx = try!(read_u16!(data, r));
+        // Relocatable files give mapping symbols as offsets into the section
+        let base = if self.hdr.elf_type == types::ET_REL { 0 } else { section.addr };
+        let mut regions: Vec<(u64, Option<::ARMMode>)> = self.symbols_full.iter()
+            .filter(|s| s.shndx as usize == section.index && s.value >= base)
+            .filter_map(|s| {
+                let mode = match s.name.split('.').next() {
+                    Some("$a") => Some(::ARMMode::ARM),
+                    Some("$t") => Some(::ARMMode::Thumb),
+                    Some("$d") => None,
+                    _ => return None,
+                };
+                Some((s.value - base, mode))
+            })
+            .collect();
+        regions.sort_by_key(|&(off, _)| off);
+        if regions.first().map_or(true, |&(off, _)| off != 0) {
+            regions.insert(0, (0, Some(default_mode)));
+        }
 
-        let mut sections = HashMap::new();
-        let mut sections_lst = Vec::new();
-        let mut sections_data = Vec::new();
+        let mut insns = Vec::new();
+        for (i, &(start, mode)) in regions.iter().enumerate() {
+            let end = regions.get(i + 1).map_or(section.data.len() as u64, |&(off, _)| off);
+            let end = cmp::min(end, section.data.len() as u64);
+            let mode = match mode {
+                Some(mode) if start < end => mode,
+                _ => continue,
+            };
+            let arch = ::Arch::ARM(::Width::W32, endian, mode, kind);
+            insns.extend(try!(::disasm::disassemble(arch, &section.data[start as usize..end as usize], section.addr.wrapping_add(start))));
+        }
+        Ok(insns)
+    }
+    /// Map a virtual address to a file offset using the PT_LOAD segments,
+    /// or the allocated sections if the file has no program headers
+    ///
+    /// Addresses that are only backed by zero-fill (e.g. `.bss`), or whose
+    /// offset would overflow a `u64`, have no file offset and yield `None`.
+    pub fn vaddr_to_offset(&self, addr: u64) -> Option<u64> {
+        if self.segments.iter().any(|s| s.progtype == types::PT_LOAD) {
+            return self.segments.iter()
+                .find(|s| s.progtype == types::PT_LOAD && addr >= s.vaddr && addr - s.vaddr < s.filesz)
+                .and_then(|s| s.offset.checked_add(addr - s.vaddr));
+        }
+        match self.section_for_addr(addr) {
+            Some(s) if !s.nobits => s.offset.checked_add(addr - s.addr),
+            _ => None,
+        }
+    }
+    /// Function symbol containing `addr`, with the offset of `addr` into it
+    ///
+    /// A function whose `[value, value + size)` range contains `addr` is
+    /// preferred. Failing that, the nearest preceding function with a size
+    /// of 0 is returned, since its extent is unknown. Both the static and
+    /// the dynamic symbol tables are searched.
+    pub fn symbol_for_addr(&self, addr: u64) -> Option<(&str, u64)> {
+        let funcs = self.symbols_full.iter().chain(self.dynamic_symbols.iter()).filter(|s| {
+            (s.sym_type == types::STT_FUNC || s.sym_type == types::STT_GNU_IFUNC) &&
+                s.shndx != types::SHN_UNDEF && s.value <= addr
+        });
 
-        let mut name_idxs = Vec::new();
-        try!(r.seek(io::SeekFrom::Start(shoff)));
-
-        for _ in 0..shnum {
-            let name = String::new();
-            let shtype: types::SectionType;
-            let flags: types::SectionFlag;
-            let addr: u64;
-            let offset: u64;
-            let size: u64;
-            let link: u32;
-            let info: u32;
-            let addralign: u64;
-            let entsize: u64;
-
-            name_idxs.push(try!(read_u32!(data, r)));
-            shtype = types::SectionType(try!(read_u32!(data, r)));
-            match class {
-                types::ELFCLASS32 => {
-                    flags = types::SectionFlag(try!(read_u32!(data, r)) as u64);
-                    addr = try!(read_u32!(data, r)) as u64;
-                    offset = try!(read_u32!(data, r)) as u64;
-                    size = try!(read_u32!(data, r)) as u64;
-                    link = try!(read_u32!(data, r));
-                    info = try!(read_u32!(data, r));
-                    addralign = try!(read_u32!(data, r)) as u64;
-                    entsize = try!(read_u32!(data, r)) as u64;
+        let mut nearest: Option<&types::Symbol> = None;
+        for sym in funcs {
+            if sym.size != 0 {
+                if addr - sym.value < sym.size {
+                    return Some((&sym.name[..], addr - sym.value));
                 }
-                types::ELFCLASS64 => {
-                    flags = types::SectionFlag(try!(read_u64!(data, r)));
-                    addr = try!(read_u64!(data, r));
-                    offset = try!(read_u64!(data, r));
-                    size = try!(read_u64!(data, r));
-                    link = try!(read_u32!(data, r));
-                    info = try!(read_u32!(data, r));
-                    addralign = try!(read_u64!(data, r));
-                    entsize = try!(read_u64!(data, r));
+            } else if nearest.map_or(true, |n| sym.value > n.value) {
+                nearest = Some(sym);
+            }
+        }
+        nearest.map(|s| (&s.name[..], addr - s.value))
+    }
+    /// Undefined symbols the file expects to be provided by others
+    ///
+    /// Taken from the dynamic symbol table, or from the static one for files
+    /// without a `.dynsym` (e.g. relocatable objects). Weak undefined symbols
+    /// are included; check their `bind` for `STB_WEAK`.
+    pub fn imports(&self) -> Vec<&types::Symbol> {
+        let table = if self.dynamic_symbols.is_empty() {
+            &self.symbols_full
+        } else {
+            &self.dynamic_symbols
+        };
+        table.iter()
+            .filter(|s| s.shndx == types::SHN_UNDEF && !s.name.is_empty())
+            .collect()
+    }
+    /// Symbols defined in the section at `index`
+    ///
+    /// Taken from the static symbol table, or from the dynamic one for
+    /// stripped files. Special indices (`SHN_UNDEF`, and reserved ones such
+    /// as `SHN_ABS` and `SHN_COMMON`) never refer to a section, so asking for
+    /// them gives no symbols.
+    pub fn symbols_in_section(&self, index: usize) -> Vec<&types::Symbol> {
+        if index == types::SHN_UNDEF as usize || index >= types::SHN_LORESERVE as usize {
+            return Vec::new();
+        }
+        let table = if self.symbols_full.is_empty() {
+            &self.dynamic_symbols
+        } else {
+            &self.symbols_full
+        };
+        table.iter().filter(|s| s.shndx as usize == index).collect()
+    }
+    /// Symbols the file makes available to others, from the dynamic symbol table
+    ///
+    /// A symbol is exported if it is defined and has `STB_GLOBAL`, `STB_WEAK`
+    /// or `STB_GNU_UNIQUE` binding. Symbols with `STV_HIDDEN` or
+    /// `STV_INTERNAL` visibility are left out, as they can't be referenced
+    /// from another module; `STV_PROTECTED` symbols are still exported.
+    pub fn exports(&self) -> Vec<&types::Symbol> {
+        self.dynamic_symbols.iter()
+            .filter(|s| {
+                (s.bind == types::STB_GLOBAL || s.bind == types::STB_WEAK || s.bind == types::STB_GNU_UNIQUE) &&
+                    s.shndx != types::SHN_UNDEF && !s.name.is_empty() &&
+                    s.vis != types::STV_HIDDEN && s.vis != types::STV_INTERNAL
+            })
+            .collect()
+    }
+    /// The file has no static symbol table (`SHT_SYMTAB`), though it may
+    /// still have a dynamic one
+    pub fn is_stripped(&self) -> bool {
+        !self.sections.iter().any(|s| s.shtype == types::SHT_SYMTAB)
+    }
+    /// Shannon entropy (bits per byte) of each named section's `data()`,
+    /// in section table order
+    ///
+    /// Zero-fill sections, and sections deferred by `parse_lazy` that have
+    /// not been loaded, report 0.
+    pub fn entropy_report(&self) -> Vec<(String, f64)> {
+        self.sections.iter()
+            .filter(|s| !s.name.is_empty())
+            .map(|s| (s.name.clone(), s.entropy()))
+            .collect()
+    }
+    /// Heuristic sign that the code has been packed or encrypted: an
+    /// executable section with entropy above 7.2 bits per byte, or an entry
+    /// point outside every executable section (including when there are no
+    /// section headers at all, as with UPX)
+    ///
+    /// A file with no entry point (0, as in relocatable objects and most
+    /// shared libraries) is only judged on entropy.
+    pub fn likely_packed(&self) -> bool {
+        let mut exec = self.sections.iter().filter(|s| s.is_executable());
+        if exec.clone().any(|s| s.entropy() > 7.2) {
+            return true;
+        }
+        let entry = self.hdr.entrypoint;
+        entry != 0 && !exec.any(|s| entry >= s.addr && entry - s.addr < s.size)
+    }
+    /// The file has DWARF debug information (any `.debug_*` or `.zdebug_*` section)
+    pub fn has_debug_info(&self) -> bool {
+        self.sections.iter().any(|s| s.name.starts_with(".debug_") || s.name.starts_with(".zdebug_"))
+    }
+    /// The file is a position-independent executable
+    ///
+    /// Both PIEs and shared libraries are `ET_DYN`; only a PIE carries
+    /// `DF_1_PIE` in its `DT_FLAGS_1` entry. `ET_EXEC` files are never PIE.
+    pub fn is_pie(&self) -> bool {
+        self.hdr.elf_type == types::ET_DYN && self.dynamic.iter().any(|e| {
+            e.tag == types::DT_FLAGS_1 && e.value & types::DF_1_PIE != 0
+        })
+    }
+    /// Address of each PLT stub paired with the name of the function it calls
+    ///
+    /// Stubs in `.plt` and `.plt.sec` are decoded to find the GOT slot they
+    /// jump through, which is matched against the `JUMP_SLOT` relocations.
+    /// Only x86-64 and AArch64 are supported; other machines, and files
+    /// parsed lazily without loading `.plt`, give an empty list.
+    pub fn plt_entries(&self) -> Vec<(u64, String)> {
+        let jump_slot = match self.hdr.machine {
+            types::EM_X86_64 => types::R_X86_64_JUMP_SLOT,
+            types::EM_AARCH64 => types::R_AARCH64_JUMP_SLOT,
+            _ => return Vec::new(),
+        };
+        let mut slots = HashMap::new();
+        for relocs in self.relocations.values() {
+            for rel in relocs.iter().filter(|r| r.rel_type == jump_slot) {
+                if let Some(sym) = self.dynamic_symbols.get(rel.sym as usize) {
+                    slots.insert(rel.offset, &sym.name);
                 }
-                _ => unreachable!(),
             }
+        }
 
-            sections_lst.push(types::SectionHeader {
-                name: name,
-                shtype: shtype,
+        let mut entries = Vec::new();
+        for section in self.sections.iter().filter(|s| s.name == ".plt" || s.name == ".plt.sec") {
+            for (i, stub) in section.data.chunks(16).enumerate() {
+                let addr = section.addr.wrapping_add((i * 16) as u64);
+                if let Some(name) = plt_got_slot(self.hdr.machine, addr, stub).and_then(|slot| slots.get(&slot)) {
+                    entries.push((addr, (*name).clone()));
+                }
+            }
+        }
+        entries.sort();
+        entries
+    }
+    /// Thread-local storage template described by the `PT_TLS` segment
+    pub fn tls(&self) -> Option<types::TlsInfo> {
+        self.segments.iter().find(|s| s.progtype == types::PT_TLS).map(|s| types::TlsInfo {
+            addr: s.vaddr,
+            offset: s.offset,
+            filesz: s.filesz,
+            memsz: s.memsz,
+            bss_size: s.memsz.saturating_sub(s.filesz),
+            align: s.align,
+        })
+    }
+    /// Section groups (COMDAT groups, e.g. of C++ template instances) of a
+    /// relocatable file
+    pub fn section_groups(&self) -> Vec<types::SectionGroup> {
+        let data = self.hdr.data;
+        let mut groups = Vec::new();
+        for section in self.sections.iter().filter(|s| s.shtype == types::SHT_GROUP) {
+            let mut words = section.data.chunks(4).filter(|w| w.len() == 4).map(|w| match data {
+                types::ELFDATA2MSB => (w[0] as u32) << 24 | (w[1] as u32) << 16 | (w[2] as u32) << 8 | w[3] as u32,
+                _ => w[0] as u32 | (w[1] as u32) << 8 | (w[2] as u32) << 16 | (w[3] as u32) << 24,
+            });
+            let flags = words.next().unwrap_or(0);
+            // Only one static symbol table is expected, so sh_link is just checked
+            let is_symtab = self.sections.get(section.link as usize).map_or(false, |s| s.shtype == types::SHT_SYMTAB);
+            let signature = match self.symbols_full.get(section.info as usize) {
+                Some(sym) if is_symtab && sym.sym_type == types::STT_SECTION => {
+                    self.sections.get(sym.shndx as usize).map(|s| s.name.clone())
+                },
+                Some(sym) if is_symtab => Some(sym.name.clone()),
+                _ => None,
+            };
+            groups.push(types::SectionGroup {
+                section: section.index,
+                symtab: section.link,
+                symbol: section.info,
+                signature: signature,
                 flags: flags,
-                addr: addr,
-                offset: offset,
-                size: size,
-                link: link,
-                info: info,
-                addralign: addralign,
-                entsize: entsize,
+                members: words.collect(),
             });
         }
+        groups
+    }
+    /// Indices of the segments covering each section, keyed by section index
+    ///
+    /// Follows the section to segment mapping of `readelf -l`, so the
+    /// mapping considers every segment type, not just PT_LOAD. Sections
+    /// outside all segments map to an empty list.
+    pub fn section_to_segment(&self) -> HashMap<usize, Vec<usize>> {
+        self.sections.iter().map(|section| {
+            let segments = self.segments.iter().enumerate()
+                .filter(|&(_, segment)| section.index != 0 && section_in_segment(section, segment))
+                .map(|(i, _)| i)
+                .collect();
+            (section.index, segments)
+        }).collect()
+    }
+    /// Overlaps, gaps and other oddities in the section and segment layout
+    ///
+    /// A gap between two sections in the same segment is only reported if
+    /// it is wider than the padding needed to reach the second one's offset,
+    /// taking that offset's largest power-of-two factor as its alignment.
+    pub fn layout_anomalies(&self) -> Vec<types::Anomaly> {
+        let mut anomalies = Vec::new();
 
-        for i in 0..shnum {
-            let off = sections_lst[i as usize].offset;
-            let size = sections_lst[i as usize].size;
-            try!(r.seek(io::SeekFrom::Start(off)));
-            let data: Vec<u8> = io::Read::by_ref(r).bytes().map(|x| x.unwrap()).take(size as usize).collect();
-            sections_data.push(data);
+        let mut in_file: Vec<&Section> = self.sections.iter()
+            .filter(|s| s.index != 0 && !s.nobits && s.size != 0)
+            .collect();
+        in_file.sort_by_key(|s| (s.offset, s.index));
+        for (i, a) in in_file.iter().enumerate() {
+            for b in in_file[i + 1..].iter().take_while(|b| b.offset < a.offset.saturating_add(a.size)) {
+                anomalies.push(types::Anomaly::SectionOverlap {
+                    first: a.index,
+                    second: b.index,
+                    start: b.offset,
+                    end: cmp::min(a.offset.saturating_add(a.size), b.offset.saturating_add(b.size)),
+                });
+            }
         }
+        let loads: Vec<(usize, &types::ProgramHeader)> = self.segments.iter().enumerate()
+            .filter(|&(_, p)| p.progtype == types::PT_LOAD)
+            .collect();
+        let load_of = |s: &Section| loads.iter().find(|&&(_, p)| section_in_segment(s, p)).map(|&(i, _)| i);
 
-        let mut symbols = HashMap::new();
-
-        for (i, section) in sections_lst.iter().enumerate() {
-            if section.shtype == types::SHT_SYMTAB {
-                let mut cur = io::Cursor::new(sections_data[i].as_slice());
-                for i in 0..(section.size / section.entsize) {
-                    try!(cur.seek(io::SeekFrom::Start(i * section.entsize)));
-                    let sym_name;
-                    let sym_addr;
-                    match class {
-                        types::ELFCLASS32 => {
-                            sym_name = try!(read_u32!(data, cur));
-                            sym_addr = try!(read_u32!(data, cur)) as u64;
-                        }
-                        types::ELFCLASS64 => {
-                            sym_name = try!(read_u32!(data, cur));
-                            let _ = try!(read_u8!(data, cur));
-                            let _ = try!(read_u8!(data, cur));
-                            let _ = try!(read_u16!(data, cur));
-                            sym_addr = try!(read_u64!(data, cur));
-                        }
-                        _ => unreachable!(),
-                    }
-                    symbols.insert(get_elf_string(&sections_data[section.link as usize], sym_name as usize), sym_addr);
+        let mut prev: Option<(&Section, u64)> = None;
+        for s in in_file.iter() {
+            if let Some((prev_sect, prev_end)) = prev {
+                // Largest power of two dividing the offset
+                let align = if s.offset == 0 { 0 } else { s.offset & s.offset.wrapping_neg() };
+                // Moving to another segment skips ahead to keep offsets and
+                // addresses congruent, so only gaps within a segment count
+                let same_segment = match (load_of(prev_sect), load_of(s)) {
+                    (Some(a), Some(b)) => a == b,
+                    _ => true,
+                };
+                if same_segment && s.offset > prev_end && s.offset - prev_end >= align {
+                    anomalies.push(types::Anomaly::Gap { start: prev_end, end: s.offset });
                 }
             }
+            let end = s.offset.saturating_add(s.size);
+            prev = match prev {
+                Some((_, prev_end)) if prev_end > end => Some((s, prev_end)),
+                _ => Some((s, end)),
+            };
         }
 
-        for i in 0..shnum {
-            sections_lst[i as usize].name = get_elf_string(&sections_data[shstrndx as usize], name_idxs[i as usize] as usize);
+        for (i, &(a_idx, a)) in loads.iter().enumerate() {
+            if i > 0 && a.vaddr < loads[i - 1].1.vaddr {
+                anomalies.push(types::Anomaly::SegmentOrder { segment: a_idx, previous: loads[i - 1].0 });
+            }
+            for &(b_idx, b) in loads[i + 1..].iter() {
+                let start = cmp::max(a.vaddr, b.vaddr);
+                let end = cmp::min(a.vaddr.saturating_add(a.memsz), b.vaddr.saturating_add(b.memsz));
+                if start < end {
+                    anomalies.push(types::Anomaly::SegmentOverlap { first: a_idx, second: b_idx, start: start, end: end });
+                }
+            }
         }
 
-        for (hdr, data) in sections_lst.into_iter().zip(sections_data.into_iter()) {
-            sections.insert(hdr.name.clone(), Section { name: hdr.name, addr: hdr.addr, offset: hdr.offset, size: hdr.size, data: data });
+        for s in self.sections.iter().filter(|s| s.index != 0 && s.is_alloc()) {
+            if s.size == 0 {
+                anomalies.push(types::Anomaly::EmptyAlloc { section: s.index, addr: s.addr });
+                continue;
+            }
+            // .tbss occupies no address space outside PT_TLS
+            let tbss = s.nobits && s.flags.contains(types::SHF_TLS);
+            if !loads.is_empty() && !tbss && !loads.iter().any(|&(_, p)| section_in_segment(s, p)) {
+                anomalies.push(types::Anomaly::OutsideSegments {
+                    section: s.index,
+                    start: s.addr,
+                    end: s.addr.saturating_add(s.size),
+                });
+            }
         }
 
-        let x = File {
-            hdr: types::FileHeader {
-                class: class,
-                data: data,
+        anomalies
+    }
+    /// The `.eh_frame_hdr` binary search table, or `None` if the file has
+    /// none (or it is malformed)
+    pub fn eh_frame_hdr(&self) -> Option<types::EhFrameHdr> {
+        let section = match self.get_section(".eh_frame_hdr") {
+            Some(s) if !s.data.is_empty() => s,
+            _ => return None,
+        };
+        let mut r = EhReader { data: &section.data, pos: 0, addr: section.addr, class: self.hdr.class, order: self.hdr.data };
+        let parse = |r: &mut EhReader| -> Result<types::EhFrameHdr, Error> {
+            let version = try!(r.u8());
+            let eh_frame_ptr_enc = try!(r.u8());
+            let fde_count_enc = try!(r.u8());
+            let table_enc = try!(r.u8());
+            if version != 1 {
+                return Err(Error::from("unsupported .eh_frame_hdr version"));
+            }
+            let eh_frame_ptr = try!(r.encoded(eh_frame_ptr_enc, section.addr));
+            let mut table = Vec::new();
+            if fde_count_enc != types::DW_EH_PE_OMIT && table_enc != types::DW_EH_PE_OMIT {
+                let count = try!(r.encoded(fde_count_enc, section.addr));
+                for _ in 0..count {
+                    let loc = try!(r.encoded(table_enc, section.addr));
+                    let fde = try!(r.encoded(table_enc, section.addr));
+                    table.push((loc, fde));
+                }
+            }
+            Ok(types::EhFrameHdr {
                 version: version,
-                os_abi: os_abi,
-                abi_version: abi_version,
-                elf_type: elf_type,
-                machine: machine,
-                entrypoint: entry,
+                eh_frame_ptr_enc: eh_frame_ptr_enc,
+                fde_count_enc: fde_count_enc,
+                table_enc: table_enc,
+                eh_frame_ptr: eh_frame_ptr,
+                table: table,
+            })
+        };
+        parse(&mut r).ok()
+    }
+    /// Whether `addr` is covered by an FDE listed in `.eh_frame_hdr`
+    ///
+    /// The nearest preceding entry of the table is found; if `.eh_frame` is
+    /// loaded its FDE's address range is then checked, otherwise being past
+    /// the start of the entry is taken as enough.
+    pub fn has_unwind_info(&self, addr: u64) -> bool {
+        let hdr = match self.eh_frame_hdr() {
+            Some(hdr) => hdr,
+            None => return false,
+        };
+        let (_, fde) = match hdr.table.binary_search_by_key(&addr, |&(loc, _)| loc) {
+            Ok(i) => hdr.table[i],
+            Err(0) => return false,
+            Err(i) => hdr.table[i - 1],
+        };
+        match self.get_section(".eh_frame") {
+            Some(eh) if !eh.data.is_empty() && fde >= eh.addr && fde - eh.addr < eh.data.len() as u64 => {
+                match parse_fde(self.hdr.class, self.hdr.data, &eh.data, eh.addr, (fde - eh.addr) as usize) {
+                    Ok((begin, range)) => addr >= begin && addr - begin < range,
+                    Err(_) => false,
+                }
             },
-            sections: sections,
-            symbols: symbols,
+            _ => true,
+        }
+    }
+    /// Path of the program interpreter (dynamic linker) from `.interp`,
+    /// or `None` for statically linked files
+    pub fn interpreter(&self) -> Option<String> {
+        self.get_section(".interp").and_then(|s| header::table_string(&s.data, 0).ok())
+    }
+    /// ABI and ISA level from `e_flags`, or `None` if this isn't a MIPS file
+    ///
+    /// 64-bit files always use N64; 32-bit files are N32 when
+    /// `EF_MIPS_ABI2` is set, and otherwise whatever `EF_MIPS_ABI` names,
+    /// defaulting to O32 as the GNU tools do.
+    pub fn mips_flags(&self) -> Option<types::MipsFlags> {
+        if self.hdr.machine != types::EM_MIPS {
+            return None;
+        }
+        let flags = self.hdr.flags;
+        let abi = if self.hdr.class == types::ELFCLASS64 {
+            types::MipsAbi::N64
+        } else if flags & types::EF_MIPS_ABI2 != 0 {
+            types::MipsAbi::N32
+        } else {
+            match flags & types::EF_MIPS_ABI {
+                types::E_MIPS_ABI_O64 => types::MipsAbi::O64,
+                types::E_MIPS_ABI_EABI32 => types::MipsAbi::Eabi32,
+                types::E_MIPS_ABI_EABI64 => types::MipsAbi::Eabi64,
+                _ => types::MipsAbi::O32,
+            }
         };
-        Ok(x)
+        Some(types::MipsFlags {
+            abi: abi,
+            isa: types::MipsIsa(flags & types::EF_MIPS_ARCH),
+            pic: flags & types::EF_MIPS_PIC != 0,
+            cpic: flags & types::EF_MIPS_CPIC != 0,
+            noreorder: flags & types::EF_MIPS_NOREORDER != 0,
+        })
+    }
+    /// EABI version and float ABI from `e_flags`, or `None` if this isn't
+    /// a 32-bit ARM file
+    ///
+    /// The float ABI bits are only defined for EABI version 5 and the
+    /// legacy GNU ABI (where `EF_ARM_VFP_FLOAT` is taken as hard-float);
+    /// for EABI versions 1 to 4 the same bits mean other things, so the
+    /// float ABI is `Unspecified`. Toolchains that set neither bit record
+    /// the convention in the `Tag_ABI_VFP_args` build attribute instead.
+    pub fn arm_flags(&self) -> Option<types::ArmFlags> {
+        if self.hdr.machine != types::EM_ARM {
+            return None;
+        }
+        let flags = self.hdr.flags;
+        let eabi = flags & types::EF_ARM_EABIMASK;
+        let float_abi = match eabi {
+            types::EF_ARM_EABI_VER5 if flags & types::EF_ARM_ABI_FLOAT_HARD != 0 => types::ArmFloatAbi::Hard,
+            types::EF_ARM_EABI_VER5 if flags & types::EF_ARM_ABI_FLOAT_SOFT != 0 => types::ArmFloatAbi::Soft,
+            types::EF_ARM_EABI_UNKNOWN if flags & types::EF_ARM_VFP_FLOAT != 0 => types::ArmFloatAbi::Hard,
+            types::EF_ARM_EABI_UNKNOWN if flags & types::EF_ARM_SOFT_FLOAT != 0 => types::ArmFloatAbi::Soft,
+            _ => types::ArmFloatAbi::Unspecified,
+        };
+        Some(types::ArmFlags {
+            eabi_version: (eabi >> 24) as u8,
+            float_abi: float_abi,
+            be8: flags & types::EF_ARM_BE8 != 0,
+        })
+    }
+    /// File-wide build attributes from the `aeabi` subsection of an ARM
+    /// `.ARM.attributes` section, as `(tag, value)` pairs in stored order
+    ///
+    /// Tags are the `types::TAG_*` values, such as `TAG_CPU_ARCH` and
+    /// `TAG_ABI_VFP_ARGS`. Attributes scoped to particular sections or
+    /// symbols, and other vendors' subsections, are skipped, and reading
+    /// stops at the first malformed entry. Empty for non-ARM files.
+    pub fn arm_attributes(&self) -> Vec<(u32, types::AttrValue)> {
+        let mut attrs = Vec::new();
+        if self.hdr.machine != types::EM_ARM {
+            return attrs;
+        }
+        if let Some(section) = self.sections.iter().find(|s| s.shtype == types::SHT_ARM_ATTRIBUTES) {
+            let _ = parse_arm_attributes(self.hdr.class, self.hdr.data, section.data(), &mut attrs);
+        }
+        attrs
+    }
+    /// Target OS/ABI from `e_ident`
+    ///
+    /// Most toolchains leave this as `SysV` whatever the platform; GNU tools
+    /// only mark a file `Linux` once it uses GNU extensions such as IFUNCs.
+    pub fn os_abi(&self) -> types::OsAbiKind {
+        types::OsAbiKind::from(self.hdr.os_abi)
+    }
+    /// File header as a labeled block in the layout of `readelf -h`
+    ///
+    /// The `Magic:` row is rebuilt from the parsed identification fields,
+    /// with the `EI_PAD` bytes shown as zero.
+    pub fn header_summary(&self) -> String {
+        let class = match self.hdr.class {
+            types::ELFCLASS32 => String::from("ELF32"),
+            types::ELFCLASS64 => String::from("ELF64"),
+            x => format!("{}", x),
+        };
+        let fields = [
+            ("Class:", class),
+            ("Data:", format!("{}", self.hdr.data)),
+            ("Version:", format!("{}", types::Version(self.hdr.ident_version as u32))),
+            ("OS/ABI:", format!("{}", self.hdr.os_abi)),
+            ("ABI Version:", format!("{}", self.hdr.abi_version)),
+            ("Type:", format!("{}", self.hdr.elf_type)),
+            ("Machine:", format!("{}", self.hdr.machine)),
+            ("Version:", format!("{:#x}", self.hdr.version.0)),
+            ("Entry point address:", format!("{:#x}", self.hdr.entrypoint)),
+            ("Flags:", format!("{:#x}", self.hdr.flags)),
+        ];
+        let mut ident = [0u8; types::EI_NIDENT];
+        ident[..4].copy_from_slice(&types::ELFMAG);
+        ident[types::EI_CLASS] = self.hdr.class.0;
+        ident[types::EI_DATA] = self.hdr.data.0;
+        ident[types::EI_VERSION] = self.hdr.ident_version;
+        ident[types::EI_OSABI] = self.hdr.os_abi.0;
+        ident[types::EI_ABIVERSION] = self.hdr.abi_version;
+        let mut out = String::from("ELF Header:\n  Magic:  ");
+        for b in ident.iter() {
+            out.push_str(&format!(" {:02x}", b));
+        }
+        out.push('\n');
+        for &(label, ref value) in fields.iter() {
+            out.push_str(&format!("  {:<35}{}\n", label, value));
+        }
+        out
     }
+}
 
-    pub fn sections(&self) -> &HashMap<String, Section> {
-        &self.sections
+impl File {
+    /// Profile and initial ARM/Thumb state of a 32-bit ARM image
+    ///
+    /// Build attributes only exist from EABI version 4 on, so for those
+    /// files `Tag_CPU_arch` and `Tag_CPU_arch_profile` pick out M-profile
+    /// and ARMv8 cores; earlier EABI versions and the legacy GNU ABI are
+    /// plain `ARMType::ARM`. M-profile cores, and files that record
+    /// `Tag_ARM_ISA_use` as 0 (no ARM instructions), only run Thumb code;
+    /// otherwise a Thumb entry point is marked by setting its low bit (as
+    /// for `BX`).
+    fn arm_kind(&self) -> (::ARMMode, ::ARMType) {
+        let mut arm_type = ::ARMType::ARM;
+        let mut thumb_only = false;
+        match self.hdr.flags & types::EF_ARM_EABIMASK {
+            types::EF_ARM_EABI_VER4 | types::EF_ARM_EABI_VER5 => {
+                let attrs = self.arm_attributes();
+                let int = |tag| attrs.iter().filter(|a| a.0 == tag).filter_map(|a| match a.1 {
+                    types::AttrValue::Int(x) => Some(x),
+                    _ => None,
+                }).next();
+                let profile = int(types::TAG_CPU_ARCH_PROFILE);
+                arm_type = match int(types::TAG_CPU_ARCH) {
+                    _ if profile == Some(b'M' as u64) => ::ARMType::MClass,
+                    Some(types::CPU_ARCH_V6_M) | Some(types::CPU_ARCH_V6S_M) | Some(types::CPU_ARCH_V7E_M) |
+                    Some(types::CPU_ARCH_V8_M_BASE) | Some(types::CPU_ARCH_V8_M_MAIN) |
+                    Some(types::CPU_ARCH_V8_1_M_MAIN) => ::ARMType::MClass,
+                    Some(arch) if arch >= types::CPU_ARCH_V8 => ::ARMType::V8,
+                    _ => ::ARMType::ARM,
+                };
+                thumb_only = int(types::TAG_ARM_ISA_USE) == Some(0);
+            },
+            _ => {},
+        }
+        let mode = if arm_type == ::ARMType::MClass || thumb_only || self.hdr.entrypoint & 1 == 1 {
+            ::ARMMode::Thumb
+        } else {
+            ::ARMMode::ARM
+        };
+        (mode, arm_type)
     }
-    pub fn symbols(&self) -> &HashMap<String, u64> {
-        &self.symbols
+}
+
+impl<'a> TryFrom<&'a [u8]> for File {
+    type Error = Error;
+
+    fn try_from(data: &'a [u8]) -> Result<File, Error> {
+        File::parse_bytes(data)
+    }
+}
+
+impl TryFrom<Vec<u8>> for File {
+    type Error = Error;
+
+    fn try_from(data: Vec<u8>) -> Result<File, Error> {
+        File::parse_bytes(&data)
     }
 }
 
@@ -245,38 +2481,462 @@ impl fmt::Display for File {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         try!(writeln!(f, "ELF file"));
         try!(write!(f, "{}", self.hdr));
+        try!(writeln!(f, "ELF segments"));
+        for segment in self.segments.iter() {
+            try!(write!(f, "{}", segment));
+        }
         try!(writeln!(f, "ELF sections"));
-        for section in self.sections.values() {
-            try!(write!(f, "{:?}", section));
+        try!(Section::fmt_table_header(f));
+        for section in self.sections.iter() {
+            try!(write!(f, "{}", section));
         }
         try!(writeln!(f, "ELF symbols"));
         let mut x: Vec<&String> = self.symbols.keys().collect();
         x.sort();
         for key in x.into_iter() {
-            try!(writeln!(f, "{}: {:#x}", key, self.symbols[key]));
+            let name = if key.is_empty() { "(null)" } else { &key[..] };
+            try!(writeln!(f, "{}: {:#x}", name, self.symbols[key]));
         }
         Ok(())
     }
 }
 
 impl Object for File {
+    fn format(&self) -> ::Format {
+        ::Format::Elf
+    }
+    /// `symbols_full`, or `dynamic_symbols` for a stripped file, without
+    /// the null symbol at index 0 of each table
+    fn symbols(&self) -> Vec<::Symbol> {
+        let table = if self.symbols_full.is_empty() { &self.dynamic_symbols } else { &self.symbols_full };
+        table.iter().skip(1).map(|s| {
+            let kind = match s.sym_type {
+                types::STT_FUNC | types::STT_GNU_IFUNC => ::SymbolKind::Function,
+                types::STT_OBJECT | types::STT_COMMON | types::STT_TLS => ::SymbolKind::Data,
+                types::STT_SECTION => ::SymbolKind::Section,
+                types::STT_FILE => ::SymbolKind::File,
+                _ => ::SymbolKind::Unknown,
+            };
+            ::Symbol {
+                name: s.name.clone(),
+                address: s.value,
+                size: s.size,
+                kind: kind,
+                global: s.bind != types::STB_LOCAL,
+                defined: s.shndx != types::SHN_UNDEF,
+            }
+        }).collect()
+    }
     fn arch(&self) -> ::Arch {
+        if let Some(arch) = self.arch_override {
+            return arch;
+        }
         let endian = match self.hdr.data {
             types::ELFDATA2LSB => ::Endianness::Little,
             types::ELFDATA2MSB => ::Endianness::Big,
             _ => return ::Arch::Unknown,
         };
+        let width = match self.hdr.class {
+            types::ELFCLASS32 => ::Width::W32,
+            types::ELFCLASS64 => ::Width::W64,
+            _ => return ::Arch::Unknown,
+        };
         match self.hdr.machine {
             types::EM_386 => ::Arch::X86(::Width::W32),
             types::EM_X86_64 => ::Arch::X86(::Width::W64),
             types::EM_PPC => ::Arch::PPC(::Width::W32, endian),
             types::EM_PPC64 => ::Arch::PPC(::Width::W64, endian),
-            types::EM_ARM => ::Arch::ARM(::Width::W32, endian, ::ARMMode::ARM, ::ARMType::ARM),
-            types::EM_AARCH64 => ::Arch::ARM(::Width::W64, endian, ::ARMMode::ARM, ::ARMType::ARM),
+            types::EM_ARM => {
+                let (mode, arm_type) = self.arm_kind();
+                ::Arch::ARM(::Width::W32, endian, mode, arm_type)
+            },
+            types::EM_AARCH64 => ::Arch::ARM(::Width::W64, endian, ::ARMMode::ARM, ::ARMType::V8),
+            types::EM_MIPS | types::EM_MIPS_RS3_LE => ::Arch::MIPS(width, endian),
+            types::EM_SPARC | types::EM_SPARC32PLUS => ::Arch::SPARC(::Width::W32, endian),
+            types::EM_SPARCV9 => ::Arch::SPARC(::Width::W64, endian),
+            types::EM_RISCV => ::Arch::RISCV(width, endian),
+            types::EM_S390 => ::Arch::S390(width, endian),
             _ => ::Arch::Unknown,
         }
     }
     fn get_section(&self, name: &str) -> Option<&Section> {
-        self.sections.get(name)
+        self.sections.iter().find(|s| s.name == name)
+    }
+    fn entry(&self) -> u64 {
+        self.hdr.entrypoint
+    }
+    fn obj_type(&self) -> ::ObjectType {
+        match self.hdr.elf_type {
+            types::ET_REL => ::ObjectType::Relocatable,
+            types::ET_EXEC => ::ObjectType::Executable,
+            types::ET_DYN => ::ObjectType::SharedObject,
+            types::ET_CORE => ::ObjectType::Core,
+            _ => ::ObjectType::Unknown,
+        }
+    }
+    fn endianness(&self) -> ::Endianness {
+        match self.hdr.data {
+            types::ELFDATA2MSB => ::Endianness::Big,
+            _ => ::Endianness::Little,
+        }
+    }
+    fn width(&self) -> ::Width {
+        match self.hdr.class {
+            types::ELFCLASS32 => ::Width::W32,
+            _ => ::Width::W64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Little-endian executable with `phnum` empty `PT_LOAD` headers, then the
+    /// section headers (null, `sections`, `.shstrtab`), then section contents
+    fn image(class: types::Class, machine: types::Machine, entry: u64, phnum: usize,
+             sections: &[(&str, types::SectionType, &[u8])]) -> Vec<u8> {
+        let wide = class == types::ELFCLASS64;
+        let word = |out: &mut Vec<u8>, x: u64| if wide {
+            out.extend_from_slice(&x.to_le_bytes());
+        } else {
+            out.extend_from_slice(&(x as u32).to_le_bytes());
+        };
+        let (ehsize, phentsize, shentsize) = if wide { (64, 56, 64) } else { (52, 32, 40) };
+        let shnum = sections.len() + 2;
+        let shoff = ehsize + phnum * phentsize;
+        let mut names = vec![0u8];
+        let mut contents = Vec::new();
+        let mut headers = Vec::new();
+        for &(name, shtype, data) in sections.iter().chain([(".shstrtab", types::SHT_STRTAB, &[][..])].iter()) {
+            headers.push((names.len(), shtype, shoff + shnum * shentsize + contents.len(), data.len()));
+            names.extend_from_slice(name.as_bytes());
+            names.push(0);
+            contents.extend_from_slice(data);
+        }
+        // .shstrtab is last, so its contents are the names just collected
+        headers.last_mut().unwrap().3 = names.len();
+        contents.extend_from_slice(&names);
+
+        let mut out = vec![0x7f, b'E', b'L', b'F', class.0, types::ELFDATA2LSB.0, 1];
+        out.resize(16, 0);
+        out.extend_from_slice(&types::ET_EXEC.0.to_le_bytes());
+        out.extend_from_slice(&machine.0.to_le_bytes());
+        out.extend_from_slice(&1u32.to_le_bytes());
+        word(&mut out, entry);
+        word(&mut out, ehsize as u64);
+        word(&mut out, shoff as u64);
+        out.extend_from_slice(&0u32.to_le_bytes());
+        for &x in [ehsize, phentsize, phnum, shentsize, shnum, shnum - 1].iter() {
+            out.extend_from_slice(&(x as u16).to_le_bytes());
+        }
+        for _ in 0..phnum {
+            out.extend_from_slice(&types::PT_LOAD.0.to_le_bytes());
+            out.resize(out.len() + phentsize - 4, 0);
+        }
+        out.resize(out.len() + shentsize, 0);
+        for &(name, shtype, offset, size) in headers.iter() {
+            out.extend_from_slice(&(name as u32).to_le_bytes());
+            out.extend_from_slice(&shtype.0.to_le_bytes());
+            word(&mut out, 0);
+            word(&mut out, 0);
+            word(&mut out, offset as u64);
+            word(&mut out, size as u64);
+            out.extend_from_slice(&[0; 8]);
+            word(&mut out, 1);
+            word(&mut out, 0);
+        }
+        out.extend_from_slice(&contents);
+        out
+    }
+
+    #[test]
+    fn symtab_with_zero_entsize_is_an_error() {
+        let data = image(types::ELFCLASS64, types::EM_X86_64, 0, 0, &[(".symtab", types::SHT_SYMTAB, &[0; 24])]);
+        assert!(File::parse(&mut io::Cursor::new(&data)).is_err());
+    }
+
+    #[test]
+    fn odd_entry_point_is_thumb() {
+        let data = image(types::ELFCLASS32, types::EM_ARM, 0x8001, 0, &[]);
+        match File::parse(&mut io::Cursor::new(&data)).unwrap().arch() {
+            ::Arch::ARM(_, _, ::ARMMode::Thumb, _) => {},
+            arch => panic!("{:?}", arch),
+        }
+        let data = image(types::ELFCLASS32, types::EM_ARM, 0x8000, 0, &[]);
+        match File::parse(&mut io::Cursor::new(&data)).unwrap().arch() {
+            ::Arch::ARM(_, _, ::ARMMode::ARM, _) => {},
+            arch => panic!("{:?}", arch),
+        }
+    }
+
+    #[test]
+    fn extended_numbering_is_read_from_section_zero() {
+        let mut data = image(types::ELFCLASS64, types::EM_X86_64, 0, 3, &[]);
+        // e_phnum = PN_XNUM, e_shnum = 0, e_shstrndx = SHN_XINDEX
+        data[0x38..0x3a].copy_from_slice(&types::PN_XNUM.to_le_bytes());
+        data[0x3c..0x3e].copy_from_slice(&0u16.to_le_bytes());
+        data[0x3e..0x40].copy_from_slice(&types::SHN_XINDEX.to_le_bytes());
+        // Section 0 (after three program headers): sh_size, sh_link, sh_info
+        let shdr0 = 64 + 3 * 56;
+        data[shdr0 + 32..shdr0 + 40].copy_from_slice(&2u64.to_le_bytes());
+        data[shdr0 + 40..shdr0 + 44].copy_from_slice(&1u32.to_le_bytes());
+        data[shdr0 + 44..shdr0 + 48].copy_from_slice(&3u32.to_le_bytes());
+        let file = File::parse(&mut io::Cursor::new(&data)).unwrap();
+        assert_eq!(file.segments.len(), 3);
+        assert!(file.get_section(".shstrtab").is_some());
+    }
+
+    #[test]
+    fn shstrndx_past_shnum_is_an_error() {
+        let mut data = image(types::ELFCLASS64, types::EM_X86_64, 0, 0, &[]);
+        assert!(File::parse(&mut io::Cursor::new(&data)).is_ok());
+        data[0x3e..0x40].copy_from_slice(&5u16.to_le_bytes());
+        assert!(File::parse(&mut io::Cursor::new(&data)).is_err());
+    }
+
+    #[test]
+    fn non_ascii_names_round_trip_through_string_table() {
+        let table = b"\0.text\0.donn\xc3\xa9es\0".to_vec();
+        let offset = find_elf_string(&table, ".données").unwrap();
+        assert_eq!(offset, 7);
+        assert_eq!(header::table_string(&table, offset).unwrap(), ".données");
+    }
+
+    #[test]
+    fn memory_image_places_segments_and_enforces_limit() {
+        let mut bytes = ::elf::ElfBuilder::new()
+            .set_entry(0x401000)
+            .add_section(".text", types::SectionFlag(types::SHF_ALLOC.0 | types::SHF_EXECINSTR.0), 0x401000, &[0xc3; 4])
+            .add_section(".data", types::SectionFlag(types::SHF_ALLOC.0 | types::SHF_WRITE.0), 0x401010, &[7; 4])
+            .build()
+            .unwrap();
+        let file = File::parse_bytes(&bytes).unwrap();
+        let image = file.memory_image(&mut io::Cursor::new(&bytes), 1 << 20).unwrap();
+        assert_eq!(image.len(), 1);
+        let (base, ref mem) = image[0];
+        assert_eq!((base, mem.len()), (0x401000, 0x1000));
+        assert_eq!(&mem[..0x14], &[0xc3, 0xc3, 0xc3, 0xc3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 7, 7, 7, 7][..]);
+
+        // Give the first segment a 1 TiB p_memsz
+        let memsz = 64 + 40;
+        bytes[memsz..memsz + 8].copy_from_slice(&(1u64 << 40).to_le_bytes());
+        let file = File::parse_bytes(&bytes).unwrap();
+        assert!(file.memory_image(&mut io::Cursor::new(&bytes), 1 << 20).is_err());
+    }
+
+    #[test]
+    fn parse_lazy_still_reads_sections_accessors_decode() {
+        let interp = b"/lib/ld-linux.so.2\0";
+        let debuglink = b"app.debug\0\0\0\x78\x56\x34\x12";
+        let bytes = ::elf::ElfBuilder::new()
+            .add_section(".interp", types::SHF_ALLOC, 0x400200, interp)
+            .add_section(".comment", types::SectionFlag(0), 0, b"GCC\0")
+            .add_section(".gnu_debuglink", types::SectionFlag(0), 0, debuglink)
+            .build()
+            .unwrap();
+        let file = File::parse_lazy(&mut io::Cursor::new(&bytes)).unwrap();
+        assert_eq!(file.interpreter().unwrap(), "/lib/ld-linux.so.2");
+        assert_eq!(file.debuglink().unwrap(), (String::from("app.debug"), 0x12345678));
+        assert!(!file.get_section(".comment").unwrap().is_loaded());
+
+        let attributes = [b'A', 15, 0, 0, 0, b'a', b'e', b'a', b'b', b'i', 0, 1, 5, 0, 0, 0];
+        let bytes = arm_image(::ARMMode::ARM, 0x8000, Some(&attributes));
+        let file = File::parse_lazy(&mut io::Cursor::new(&bytes)).unwrap();
+        assert!(file.get_section(".ARM.attributes").unwrap().is_loaded());
+    }
+
+    /// Offset of the header of section `index` in a little-endian file
+    fn shdr(bytes: &[u8], index: usize) -> usize {
+        let (shoff, shentsize) = match types::Class(bytes[types::EI_CLASS]) {
+            types::ELFCLASS32 => (u32::from_le_bytes([bytes[0x20], bytes[0x21], bytes[0x22], bytes[0x23]]) as usize, 40),
+            _ => {
+                let mut word = [0u8; 8];
+                word.copy_from_slice(&bytes[0x28..0x30]);
+                (u64::from_le_bytes(word) as usize, 64)
+            },
+        };
+        shoff + index * shentsize
+    }
+
+    /// Change the type of section `index` in a little-endian file from the
+    /// builder, which writes every section as `SHT_PROGBITS`
+    fn set_shtype(bytes: &mut [u8], index: usize, shtype: types::SectionType) {
+        let at = shdr(bytes, index) + 4;
+        bytes[at..at + 4].copy_from_slice(&shtype.0.to_le_bytes());
+    }
+
+    fn arm_image(mode: ::ARMMode, entry: u64, attributes: Option<&[u8]>) -> Vec<u8> {
+        let mut builder = ::elf::ElfBuilder::new();
+        builder.set_arch(::Arch::ARM(::Width::W32, ::Endianness::Little, mode, ::ARMType::ARM))
+            .set_entry(entry)
+            .add_section(".text", types::SectionFlag(types::SHF_ALLOC.0 | types::SHF_EXECINSTR.0), entry & !1, &[0; 8]);
+        if let Some(attributes) = attributes {
+            builder.add_section(".ARM.attributes", types::SectionFlag(0), 0, attributes);
+        }
+        let mut bytes = builder.build().unwrap();
+        if attributes.is_some() {
+            set_shtype(&mut bytes, 2, types::SHT_ARM_ATTRIBUTES);
+        }
+        bytes
+    }
+
+    #[test]
+    fn thumb_entry_point_gives_thumb_mode() {
+        let file = File::parse_bytes(&arm_image(::ARMMode::Thumb, 0x8000, None)).unwrap();
+        assert_eq!(file.hdr.flags & types::EF_ARM_EABIMASK, types::EF_ARM_EABI_VER5);
+        assert_eq!(file.entry(), 0x8001);
+        assert_eq!(file.arch(), ::Arch::ARM(::Width::W32, ::Endianness::Little, ::ARMMode::Thumb, ::ARMType::ARM));
+
+        let file = File::parse_bytes(&arm_image(::ARMMode::ARM, 0x8000, None)).unwrap();
+        assert_eq!(file.arch(), ::Arch::ARM(::Width::W32, ::Endianness::Little, ::ARMMode::ARM, ::ARMType::ARM));
+    }
+
+    #[test]
+    fn m_profile_firmware_is_thumb_mclass() {
+        // aeabi, file scope: Tag_CPU_arch v7E-M, Tag_CPU_arch_profile 'M'
+        let attributes = [b'A', 19, 0, 0, 0, b'a', b'e', b'a', b'b', b'i', 0,
+                          1, 9, 0, 0, 0, 6, 13, 7, b'M'];
+        let file = File::parse_bytes(&arm_image(::ARMMode::ARM, 0x0800_0100, Some(&attributes))).unwrap();
+        assert_eq!(file.arm_attributes(), vec![
+            (types::TAG_CPU_ARCH, types::AttrValue::Int(13)),
+            (types::TAG_CPU_ARCH_PROFILE, types::AttrValue::Int(b'M' as u64)),
+        ]);
+        assert_eq!(file.arch(), ::Arch::ARM(::Width::W32, ::Endianness::Little, ::ARMMode::Thumb, ::ARMType::MClass));
+    }
+
+    #[test]
+    fn same_named_relocation_sections_are_kept_apart() {
+        // One R_X86_64_RELATIVE entry (symbol 0, type 8) per section
+        let rela = |offset: u64, addend: i64| {
+            let mut entry = offset.to_le_bytes().to_vec();
+            entry.extend_from_slice(&8u64.to_le_bytes());
+            entry.extend_from_slice(&addend.to_le_bytes());
+            entry
+        };
+        let mut bytes = ::elf::ElfBuilder::new()
+            .add_section(".rela.text", types::SectionFlag(0), 0, &rela(0x1000, 1))
+            .add_section(".rela.text", types::SectionFlag(0), 0, &rela(0x2000, 2))
+            .build()
+            .unwrap();
+        for index in 1..3 {
+            set_shtype(&mut bytes, index, types::SHT_RELA);
+            let entsize = shdr(&bytes, index) + 56;
+            bytes[entsize..entsize + 8].copy_from_slice(&24u64.to_le_bytes());
+        }
+        let file = File::parse_bytes(&bytes).unwrap();
+        assert_eq!(file.relocations().len(), 2);
+        let resolved: Vec<(u64, i64)> = file.resolved_relocations().iter().map(|r| (r.offset, r.addend)).collect();
+        assert_eq!(resolved, vec![(0x1000, 1), (0x2000, 2)]);
+        assert_eq!(file.relocations_named(".rela.text").unwrap()[0].offset, 0x1000);
+    }
+
+    #[test]
+    #[cfg(feature = "compression")]
+    fn parse_compressed_limits_decompressed_size() {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+
+        let bytes = ::elf::ElfBuilder::new().set_entry(0x1000).build().unwrap();
+        let mut gz = GzEncoder::new(Vec::new(), Compression::default());
+        io::Write::write_all(&mut gz, &bytes).unwrap();
+        let gz = gz.finish().unwrap();
+        assert_eq!(File::parse_compressed(&gz[..]).unwrap().hdr.entrypoint, 0x1000);
+        assert!(File::parse_compressed_limited(&gz[..], bytes.len() as u64).is_ok());
+        assert!(File::parse_compressed_limited(&gz[..], bytes.len() as u64 - 1).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "compression")]
+    fn compressed_section_sizes_are_checked_against_the_input() {
+        use flate2::write::ZlibEncoder;
+        use flate2::Compression;
+
+        let text = [0x90u8; 256];
+        let mut z = ZlibEncoder::new(Vec::new(), Compression::default());
+        io::Write::write_all(&mut z, &text).unwrap();
+        let z = z.finish().unwrap();
+        let chdr = |size: u64| {
+            let mut data = types::ELFCOMPRESS_ZLIB.to_le_bytes().to_vec();
+            data.extend_from_slice(&[0; 4]);
+            data.extend_from_slice(&size.to_le_bytes());
+            data.extend_from_slice(&1u64.to_le_bytes());
+            data.extend_from_slice(&z);
+            data
+        };
+        let zdebug = |size: u64| {
+            let mut data = types::ZDEBUG_MAGIC.to_vec();
+            data.extend_from_slice(&size.to_be_bytes());
+            data.extend_from_slice(&z);
+            data
+        };
+        let image = |chdr: &[u8], zdebug: &[u8]| {
+            ::elf::ElfBuilder::new()
+                .add_section(".debug_info", types::SHF_COMPRESSED, 0, chdr)
+                .add_section(".zdebug_line", types::SectionFlag(0), 0, zdebug)
+                .build()
+                .unwrap()
+        };
+
+        let file = File::parse_bytes(&image(&chdr(256), &zdebug(256))).unwrap();
+        assert_eq!(file.get_section(".debug_info").unwrap().data(), &text[..]);
+        assert_eq!(file.get_section(".debug_line").unwrap().data(), &text[..]);
+        assert!(File::parse_bytes(&image(&chdr(1 << 40), &zdebug(256))).is_err());
+        assert!(File::parse_bytes(&image(&chdr(256), &zdebug(1 << 40))).is_err());
+    }
+
+    #[test]
+    fn write_round_trips_bytes_outside_headers_and_sections() {
+        let mut bytes = ::elf::ElfBuilder::new()
+            .set_entry(0x401000)
+            .add_section(".text", types::SectionFlag(types::SHF_ALLOC.0 | types::SHF_EXECINSTR.0), 0x401000, &[0xc3; 4])
+            .build()
+            .unwrap();
+        bytes[types::EI_NIDENT - 1] = 0xaa;
+        bytes.extend_from_slice(b"trailing signature");
+        for &lazy in [false, true].iter() {
+            let file = if lazy { File::parse_bytes_lazy(&bytes) } else { File::parse_bytes(&bytes) }.unwrap();
+            let mut out = io::Cursor::new(Vec::new());
+            file.write(&mut io::Cursor::new(&bytes), &mut out).unwrap();
+            assert!(out.get_ref() == &bytes);
+        }
+
+        let mut file = File::parse_bytes(&bytes).unwrap();
+        let text = file.sections.iter().position(|s| s.name == ".text").unwrap();
+        file.sections[text].set_data(vec![0x90; 4]).unwrap();
+        let mut out = io::Cursor::new(Vec::new());
+        file.write(&mut io::Cursor::new(&bytes), &mut out).unwrap();
+        let edited = File::parse_bytes(out.get_ref()).unwrap();
+        assert_eq!(edited.get_section(".text").unwrap().data(), &[0x90; 4][..]);
+        assert!(out.get_ref().ends_with(b"trailing signature"));
+    }
+
+    #[test]
+    fn section_data_in_rejects_a_different_buffer() {
+        let bytes = ::elf::ElfBuilder::new()
+            .add_section(".comment", types::SectionFlag(0), 0, b"GCC\0")
+            .build()
+            .unwrap();
+        let file = File::parse_bytes_lazy(&bytes).unwrap();
+        let comment = file.get_section(".comment").unwrap();
+        assert_eq!(file.section_data_in(comment, &bytes), Some(&b"GCC\0"[..]));
+        assert_eq!(file.section_data_in(comment, &bytes[..bytes.len() - 1]), None);
+    }
+
+    #[test]
+    fn header_summary_shows_magic_and_both_versions() {
+        let mut bytes = ::elf::ElfBuilder::new().build().unwrap();
+        bytes[types::EI_VERSION] = 0;
+        let summary = File::parse_bytes(&bytes).unwrap().header_summary();
+        let lines: Vec<&str> = summary.lines().collect();
+        assert_eq!(lines[1], "  Magic:   7f 45 4c 46 02 01 00 00 00 00 00 00 00 00 00 00");
+        assert_eq!(lines[4], format!("  {:<35}{}", "Version:", "invalid"));
+        assert_eq!(lines[9], format!("  {:<35}{}", "Version:", "0x1"));
+    }
+
+    #[test]
+    fn unknown_data_and_section_types_show_their_value() {
+        assert_eq!(types::Data(7).to_string(), "Unknown (0x7)");
+        assert_eq!(format!("{:<20}|", types::SectionType(0x1234)), "Unknown (0x1234)    |");
     }
 }
@@ -0,0 +1,129 @@
+//! Parsing from a `tokio` reader without blocking
+
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncSeek, ReadBuf};
+use elf::File;
+use Error;
+
+/// Size of each read from the underlying reader
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Largest input `File::parse_async` will buffer (1 GiB)
+pub const DEFAULT_MAX_LEN: u64 = 1 << 30;
+
+enum State {
+    Start,
+    Seeking,
+    Reading,
+    Done,
+}
+
+/// Future returned by `File::parse_async`
+pub struct ParseFuture<'a, R: 'a> {
+    r: &'a mut R,
+    state: State,
+    buf: Vec<u8>,
+    chunk: Vec<u8>,
+    max_len: u64,
+}
+
+impl File {
+    /// Parse an ELF file from an async reader
+    ///
+    /// The whole input is buffered in memory, read in large sequential
+    /// chunks, and then parsed with `parse_bytes`, so the many small seeks
+    /// of the section parsing never reach the reader. Inputs larger than
+    /// `DEFAULT_MAX_LEN` are rejected; see `parse_async_limited`.
+    pub fn parse_async<'a, R: AsyncRead + AsyncSeek + Unpin>(r: &'a mut R) -> ParseFuture<'a, R> {
+        File::parse_async_limited(r, DEFAULT_MAX_LEN)
+    }
+
+    /// Like `parse_async`, but failing once more than `max_len` bytes have
+    /// been read instead of buffering the rest
+    pub fn parse_async_limited<'a, R: AsyncRead + AsyncSeek + Unpin>(r: &'a mut R, max_len: u64) -> ParseFuture<'a, R> {
+        ParseFuture {
+            r: r,
+            state: State::Start,
+            buf: Vec::new(),
+            chunk: vec![0u8; CHUNK_SIZE],
+            max_len: max_len,
+        }
+    }
+}
+
+impl<'a, R: AsyncRead + AsyncSeek + Unpin> Future for ParseFuture<'a, R> {
+    type Output = Result<File, Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<File, Error>> {
+        let this = &mut *self;
+        loop {
+            match this.state {
+                State::Start => {
+                    if let Err(e) = Pin::new(&mut *this.r).start_seek(io::SeekFrom::Start(0)) {
+                        this.state = State::Done;
+                        return Poll::Ready(Err(Error::from(e)));
+                    }
+                    this.state = State::Seeking;
+                },
+                State::Seeking => match Pin::new(&mut *this.r).poll_complete(cx) {
+                    Poll::Pending => return Poll::Pending,
+                    Poll::Ready(Err(e)) => {
+                        this.state = State::Done;
+                        return Poll::Ready(Err(Error::from(e)));
+                    },
+                    Poll::Ready(Ok(_)) => this.state = State::Reading,
+                },
+                State::Reading => {
+                    let mut rb = ReadBuf::new(&mut this.chunk);
+                    match Pin::new(&mut *this.r).poll_read(cx, &mut rb) {
+                        Poll::Pending => return Poll::Pending,
+                        Poll::Ready(Err(e)) => {
+                            this.state = State::Done;
+                            return Poll::Ready(Err(Error::from(e)));
+                        },
+                        Poll::Ready(Ok(())) => {
+                            if rb.filled().is_empty() {
+                                this.state = State::Done;
+                                return Poll::Ready(File::parse_bytes(&this.buf));
+                            }
+                            if (this.buf.len() + rb.filled().len()) as u64 > this.max_len {
+                                this.state = State::Done;
+                                return Poll::Ready(Err(Error::Other(format!("input is larger than {} bytes", this.max_len))));
+                            }
+                            this.buf.extend_from_slice(rb.filled());
+                        },
+                    }
+                },
+                State::Done => panic!("ParseFuture polled after completion"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::task::Waker;
+    use elf::ElfBuilder;
+
+    /// Drive `f` to completion; an in-memory reader is never pending
+    fn block_on<F: Future + Unpin>(mut f: F) -> F::Output {
+        let mut cx = Context::from_waker(Waker::noop());
+        match Pin::new(&mut f).poll(&mut cx) {
+            Poll::Ready(x) => x,
+            Poll::Pending => panic!("in-memory read was pending"),
+        }
+    }
+
+    #[test]
+    fn parse_async_respects_max_len() {
+        let bytes = ElfBuilder::new().set_entry(0x1000).build().unwrap();
+        let file = block_on(File::parse_async(&mut io::Cursor::new(bytes.clone()))).unwrap();
+        assert_eq!(file.hdr.entrypoint, 0x1000);
+        let short = bytes.len() as u64 - 1;
+        assert!(block_on(File::parse_async_limited(&mut io::Cursor::new(bytes), short)).is_err());
+    }
+}
@@ -0,0 +1,315 @@
+use std::vec::Vec;
+use std::string::String;
+use elf::types;
+use source::ByteSource;
+use Error;
+
+/// Sequential reader over a `ByteSource` in the file's byte order
+struct Reader<'a, S: 'a + ?Sized> {
+    src: &'a mut S,
+    off: u64,
+    data: types::Data,
+}
+
+impl<'a, S: ByteSource + ?Sized> Reader<'a, S> {
+    fn bytes<'b>(&mut self, buf: &'b mut [u8]) -> Result<&'b [u8], Error> {
+        try!(self.src.read_at(self.off, buf));
+        self.off += buf.len() as u64;
+        Ok(buf)
+    }
+    fn u16(&mut self) -> Result<u16, Error> {
+        let mut buf = [0u8; 2];
+        let b = try!(self.bytes(&mut buf));
+        match self.data {
+            types::ELFDATA2LSB => Ok(b[0] as u16 | (b[1] as u16) << 8),
+            types::ELFDATA2MSB => Ok((b[0] as u16) << 8 | b[1] as u16),
+            _ => Err(Error::InvalidEndianness),
+        }
+    }
+    fn u32(&mut self) -> Result<u32, Error> {
+        let (a, b) = (try!(self.u16()) as u32, try!(self.u16()) as u32);
+        match self.data {
+            types::ELFDATA2LSB => Ok(a | b << 16),
+            _ => Ok(a << 16 | b),
+        }
+    }
+    fn u64(&mut self) -> Result<u64, Error> {
+        let (a, b) = (try!(self.u32()) as u64, try!(self.u32()) as u64);
+        match self.data {
+            types::ELFDATA2LSB => Ok(a | b << 32),
+            _ => Ok(a << 32 | b),
+        }
+    }
+    /// A 32 or 64-bit word depending on the file class
+    fn word(&mut self, class: types::Class) -> Result<u64, Error> {
+        match class {
+            types::ELFCLASS32 => Ok(try!(self.u32()) as u64),
+            _ => self.u64(),
+        }
+    }
+}
+
+/// Everything in the ELF, program and section headers, with section names
+/// resolved from the section header string table
+pub struct Headers {
+    pub hdr: types::FileHeader,
+    pub segments: Vec<types::ProgramHeader>,
+    pub sections: Vec<types::SectionHeader>,
+    /// File offset of the program header table (`e_phoff`)
+    pub phoff: u64,
+    /// File offset of the section header table (`e_shoff`)
+    pub shoff: u64,
+    /// Index of the section header string table (after `SHN_XINDEX` is resolved)
+    pub shstrndx: u32,
+    pub warnings: Vec<types::Warning>,
+}
+
+fn parse_section_header<S: ByteSource + ?Sized>(r: &mut Reader<S>, class: types::Class) -> Result<(u32, types::SectionHeader), Error> {
+    let name_idx = try!(r.u32());
+    let shtype = types::SectionType(try!(r.u32()));
+    let flags = types::SectionFlag(try!(r.word(class)));
+    let addr = try!(r.word(class));
+    let offset = try!(r.word(class));
+    let size = try!(r.word(class));
+    let link = try!(r.u32());
+    let info = try!(r.u32());
+    let addralign = try!(r.word(class));
+    let entsize = try!(r.word(class));
+
+    Ok((name_idx, types::SectionHeader {
+        name: String::new(),
+        shtype: shtype,
+        flags: flags,
+        addr: addr,
+        offset: offset,
+        size: size,
+        link: link,
+        info: info,
+        addralign: addralign,
+        entsize: entsize,
+    }))
+}
+
+/// Name starting at `start` in a string table, up to its NUL (or the end),
+/// decoded as UTF-8 with invalid sequences replaced by U+FFFD
+pub fn table_string(table: &[u8], start: usize) -> Result<String, Error> {
+    if start > table.len() {
+        return Err(Error::from("string table index out of range"));
+    }
+    let end = table[start..].iter().position(|x| *x == 0).map_or(table.len(), |n| start + n);
+    Ok(String::from_utf8_lossy(&table[start..end]).into_owned())
+}
+
+/// The ELF header, with the fields locating the header tables alongside
+struct Ehdr {
+    hdr: types::FileHeader,
+    phoff: u64,
+    shoff: u64,
+    phnum: u32,
+    shnum: u64,
+    shstrndx: u32,
+    phentsize: u64,
+    shentsize: u64,
+}
+
+/// Read the ELF header from `src` with two reads: `e_ident`, then the rest
+/// of the fixed-size header for the file's class
+#[allow(unused_variables)]
+fn parse_ehdr<S: ByteSource + ?Sized>(src: &mut S) -> Result<Ehdr, Error> {
+    let mut buf = [0u8; 64];
+    try!(src.read_at(0, &mut buf[..types::EI_NIDENT]));
+    if buf[0..4] != types::ELFMAG {
+        try!(Err(Error::BadMagic));
+    }
+
+    let class = types::Class(buf[types::EI_CLASS]);
+    let data = types::Data(buf[types::EI_DATA]);
+    let ident_version = buf[types::EI_VERSION];
+    let os_abi = types::OsAbi(buf[types::EI_OSABI]);
+    let abi_version = buf[types::EI_ABIVERSION];
+    let size = match class {
+        types::ELFCLASS32 => 52,
+        types::ELFCLASS64 => 64,
+        _ => return Err(Error::InvalidClass),
+    };
+    try!(src.read_at(types::EI_NIDENT as u64, &mut buf[types::EI_NIDENT..size]));
+
+    let mut fixed = &buf[..size];
+    let mut r = Reader { src: &mut fixed, off: types::EI_NIDENT as u64, data: data };
+
+    let elf_type = types::Type(try!(r.u16()));
+    let machine = types::Machine(try!(r.u16()));
+    let version = types::Version(try!(r.u32()));
+    let entry = try!(r.word(class));
+    let phoff = try!(r.word(class));
+    let shoff = try!(r.word(class));
+    let flags = try!(r.u32());
+    let ehsize = try!(r.u16());
+    let phentsize = try!(r.u16());
+    let phnum = try!(r.u16()) as u32;
+    let shentsize = try!(r.u16());
+    let shnum = try!(r.u16()) as u64;
+    let shstrndx = try!(r.u16()) as u32;
+
+    Ok(Ehdr {
+        hdr: types::FileHeader {
+            class: class,
+            data: data,
+            ident_version: ident_version,
+            version: version,
+            os_abi: os_abi,
+            abi_version: abi_version,
+            elf_type: elf_type,
+            machine: machine,
+            entrypoint: entry,
+            flags: flags,
+        },
+        phoff: phoff,
+        shoff: shoff,
+        phnum: phnum,
+        shnum: shnum,
+        shstrndx: shstrndx,
+        phentsize: phentsize as u64,
+        shentsize: shentsize as u64,
+    })
+}
+
+/// Parse only the ELF header from `src`, without touching the program or
+/// section header tables
+pub fn parse_header<S: ByteSource + ?Sized>(src: &mut S) -> Result<types::FileHeader, Error> {
+    Ok(try!(parse_ehdr(src)).hdr)
+}
+
+/// Parse the ELF header, program headers and section headers from `src`
+pub fn parse<S: ByteSource + ?Sized>(src: &mut S) -> Result<Headers, Error> {
+    let file_len = try!(src.len());
+
+    let ehdr = try!(parse_ehdr(src));
+    let hdr = ehdr.hdr;
+    let class = hdr.class;
+    let (phoff, shoff) = (ehdr.phoff, ehdr.shoff);
+    let (mut phnum, mut shnum, mut shstrndx) = (ehdr.phnum, ehdr.shnum, ehdr.shstrndx);
+    let (phentsize, shentsize) = (ehdr.phentsize, ehdr.shentsize);
+    let (phdr_size, shdr_size) = match class {
+        types::ELFCLASS32 => (32, 40),
+        _ => (56, 64),
+    };
+
+    let mut r = Reader { src: src, off: 0, data: hdr.data };
+    let mut warnings = Vec::new();
+    if hdr.version != types::EV_CURRENT {
+        warnings.push(types::Warning::BadVersion(hdr.version));
+    }
+
+    // Counts and indexes that don't fit in the header live in section 0
+    if shoff != 0 && (shnum == 0 || shstrndx == types::SHN_XINDEX as u32 || phnum == types::PN_XNUM as u32) {
+        if shoff.checked_add(shdr_size).map_or(true, |end| end > file_len) {
+            try!(Err(Error::Truncated { offset: shoff }));
+        }
+        r.off = shoff;
+        let (_, shdr0) = try!(parse_section_header(&mut r, class));
+        if shnum == 0 {
+            shnum = shdr0.size;
+        }
+        if shstrndx == types::SHN_XINDEX as u32 {
+            shstrndx = shdr0.link;
+        }
+        if phnum == types::PN_XNUM as u32 {
+            phnum = shdr0.info;
+        }
+    }
+
+    // Reject header tables that claim more entries than the input holds
+    // before looping over them. Entries may be larger than the structures
+    // read from them, but not smaller.
+    if (phnum != 0 && phentsize < phdr_size) || (shnum != 0 && shentsize < shdr_size) {
+        try!(Err(Error::InvalidEntsize));
+    }
+    if phnum != 0 && phentsize != phdr_size {
+        warnings.push(types::Warning::PhentsizeMismatch { expected: phdr_size, found: phentsize });
+    }
+    if shnum != 0 && shentsize != shdr_size {
+        warnings.push(types::Warning::ShentsizeMismatch { expected: shdr_size, found: shentsize });
+    }
+    if phnum != 0 && (phnum as u64).checked_mul(phentsize).and_then(|n| n.checked_add(phoff)).map_or(true, |end| end > file_len) {
+        try!(Err(Error::Truncated { offset: phoff }));
+    }
+    if shnum != 0 && shnum.checked_mul(shentsize).and_then(|n| n.checked_add(shoff)).map_or(true, |end| end > file_len) {
+        try!(Err(Error::Truncated { offset: shoff }));
+    }
+
+    let mut segments = Vec::new();
+    for i in 0..phnum as u64 {
+        r.off = phoff + i * phentsize;
+        let progtype = types::ProgType(try!(r.u32()));
+        let (offset, vaddr, paddr, filesz, memsz, flags, align);
+        match class {
+            types::ELFCLASS32 => {
+                offset = try!(r.u32()) as u64;
+                vaddr = try!(r.u32()) as u64;
+                paddr = try!(r.u32()) as u64;
+                filesz = try!(r.u32()) as u64;
+                memsz = try!(r.u32()) as u64;
+                flags = types::ProgFlag(try!(r.u32()));
+                align = try!(r.u32()) as u64;
+            }
+            _ => {
+                flags = types::ProgFlag(try!(r.u32()));
+                offset = try!(r.u64());
+                vaddr = try!(r.u64());
+                paddr = try!(r.u64());
+                filesz = try!(r.u64());
+                memsz = try!(r.u64());
+                align = try!(r.u64());
+            }
+        }
+
+        segments.push(types::ProgramHeader {
+            progtype: progtype,
+            offset: offset,
+            vaddr: vaddr,
+            paddr: paddr,
+            filesz: filesz,
+            memsz: memsz,
+            flags: flags,
+            align: align,
+        });
+    }
+
+    let mut sections = Vec::new();
+    let mut name_idxs = Vec::new();
+    for i in 0..shnum {
+        r.off = shoff + i * shentsize;
+        let (name_idx, shdr) = try!(parse_section_header(&mut r, class));
+        name_idxs.push(name_idx);
+        sections.push(shdr);
+    }
+
+    if (shstrndx as u64) < shnum {
+        let strtab_hdr = &sections[shstrndx as usize];
+        let mut strtab = Vec::new();
+        if strtab_hdr.shtype != types::SHT_NOBITS {
+            match strtab_hdr.offset.checked_add(strtab_hdr.size) {
+                Some(end) if end <= file_len => {},
+                _ => try!(Err(Error::Truncated { offset: strtab_hdr.offset })),
+            }
+            strtab.resize(strtab_hdr.size as usize, 0);
+            try!(r.src.read_at(strtab_hdr.offset, &mut strtab));
+        }
+        for (section, name_idx) in sections.iter_mut().zip(name_idxs.into_iter()) {
+            section.name = try!(table_string(&strtab, name_idx as usize));
+        }
+    } else if shstrndx != types::SHN_UNDEF as u32 {
+        try!(Err(Error::from("section name table index out of range")));
+    }
+
+    Ok(Headers {
+        hdr: hdr,
+        segments: segments,
+        sections: sections,
+        phoff: phoff,
+        shoff: shoff,
+        shstrndx: shstrndx,
+        warnings: warnings,
+    })
+}
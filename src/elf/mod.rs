@@ -1,4 +1,11 @@
+pub mod builder;
+#[cfg(feature = "std")]
 pub mod file;
+#[cfg(feature = "async")]
+pub mod future;
+pub mod header;
 pub mod types;
 
+pub use self::builder::ElfBuilder;
+#[cfg(feature = "std")]
 pub use self::file::File;
@@ -1,4 +1,9 @@
 use std::fmt;
+use std::str;
+use std::string::String;
+use std::vec::Vec;
+#[cfg(feature = "serde")]
+use serde::Serialize;
 
 /// Length of ELF identification fields (platform-independent)
 pub const EI_NIDENT: usize = 16;
@@ -37,7 +42,7 @@ impl fmt::Display for Class {
             ELFCLASSNONE => "invalid",
             ELFCLASS32 => "32-bit",
             ELFCLASS64 => "64-bit",
-            _ => "unknown",
+            _ => return write!(f, "Unknown ({:#x})", self.0),
         };
         write!(f, "{}", str)
     }
@@ -65,7 +70,7 @@ impl fmt::Display for Data {
             ELFDATANONE => "invalid",
             ELFDATA2LSB => "2's complement, little endian",
             ELFDATA2MSB => "2's complement, big endian",
-            _ => "unknown",
+            _ => return write!(f, "Unknown ({:#x})", self.0),
         };
         write!(f, "{}", str)
     }
@@ -83,6 +88,13 @@ pub const EV_NONE: Version = Version(0);
 /// Current version
 pub const EV_CURRENT: Version = Version(1);
 
+impl Version {
+    /// The `e_version` value as stored
+    pub fn raw(&self) -> u32 {
+        self.0
+    }
+}
+
 impl fmt::Debug for Version {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{:#x}", self.0)
@@ -159,12 +171,37 @@ impl fmt::Display for OsAbi {
             ELFOSABI_ARM_AEABI => "ARM EABI",
             ELFOSABI_ARM => "ARM",
             ELFOSABI_STANDALONE => "Standalone",
-            _ => "unknown",
+            _ => return write!(f, "Unknown ({:#x})", self.0),
         };
         write!(f, "{}", str)
     }
 }
 
+/// The common values of `OsAbi`, for matching on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OsAbiKind {
+    SysV,
+    /// Linux, or GNU extensions on any system (`ELFOSABI_GNU`)
+    Linux,
+    FreeBSD,
+    NetBSD,
+    Solaris,
+    Other(u8),
+}
+
+impl From<OsAbi> for OsAbiKind {
+    fn from(abi: OsAbi) -> OsAbiKind {
+        match abi {
+            ELFOSABI_SYSV => OsAbiKind::SysV,
+            ELFOSABI_LINUX => OsAbiKind::Linux,
+            ELFOSABI_FREEBSD => OsAbiKind::FreeBSD,
+            ELFOSABI_NETBSD => OsAbiKind::NetBSD,
+            ELFOSABI_SOLARIS => OsAbiKind::Solaris,
+            OsAbi(x) => OsAbiKind::Other(x),
+        }
+    }
+}
+
 /// ELF file machine architecture
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub struct Machine(pub u16);
@@ -326,6 +363,8 @@ pub const EM_TILEPRO: Machine = Machine(188);
 pub const EM_MICROBLAZE: Machine = Machine(189);
 /// Tilera TILE-Gx
 pub const EM_TILEGX: Machine = Machine(191);
+/// RISC-V
+pub const EM_RISCV: Machine = Machine(243);
 /// Alpha
 pub const EM_ALPHA: Machine = Machine(0x9026);
 
@@ -417,13 +456,191 @@ impl fmt::Display for Machine {
             EM_TILEPRO => "Tilera TILEPro",
             EM_MICROBLAZE => "Xilinx MicroBlaze",
             EM_TILEGX => "Tilera TILE-Gx",
+            EM_RISCV => "RISC-V",
             EM_ALPHA => "Alpha",
+            _ => return write!(f, "Unknown ({:#x})", self.0),
+        };
+        write!(f, "{}", str)
+    }
+}
+
+/// Mask for the ARM EABI version in e_flags
+pub const EF_ARM_EABIMASK: u32 = 0xff000000;
+/// ARM EABI version unknown (legacy GNU ABI)
+pub const EF_ARM_EABI_UNKNOWN: u32 = 0x00000000;
+/// ARM EABI version 1
+pub const EF_ARM_EABI_VER1: u32 = 0x01000000;
+/// ARM EABI version 2
+pub const EF_ARM_EABI_VER2: u32 = 0x02000000;
+/// ARM EABI version 3
+pub const EF_ARM_EABI_VER3: u32 = 0x03000000;
+/// ARM EABI version 4
+pub const EF_ARM_EABI_VER4: u32 = 0x04000000;
+/// ARM EABI version 5
+pub const EF_ARM_EABI_VER5: u32 = 0x05000000;
+/// ARM BE8 (byte-invariant big-endian) code
+pub const EF_ARM_BE8: u32 = 0x00800000;
+/// ARM soft-float ABI (EABI v5)
+pub const EF_ARM_ABI_FLOAT_SOFT: u32 = 0x00000200;
+/// ARM hard-float ABI (EABI v5)
+pub const EF_ARM_ABI_FLOAT_HARD: u32 = 0x00000400;
+/// ARM software floating point (legacy GNU ABI)
+pub const EF_ARM_SOFT_FLOAT: u32 = 0x00000200;
+/// ARM VFP floating point (legacy GNU ABI)
+pub const EF_ARM_VFP_FLOAT: u32 = 0x00000400;
+
+/// Floating-point calling convention of an ARM file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArmFloatAbi {
+    /// Arguments in integer registers (`-mfloat-abi=soft` or `softfp`)
+    Soft,
+    /// Arguments in VFP registers (`-mfloat-abi=hard`)
+    Hard,
+    /// Neither bit is set, or the EABI version gives them no meaning
+    Unspecified,
+}
+
+/// ARM `e_flags`, decoded
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ArmFlags {
+    /// EABI version (0 for the legacy GNU ABI)
+    pub eabi_version: u8,
+    pub float_abi: ArmFloatAbi,
+    /// Byte-invariant big-endian (BE8) code
+    pub be8: bool,
+}
+
+/// Attributes that apply to the whole file (the only scope
+/// `File::arm_attributes` reads)
+pub const TAG_FILE: u32 = 1;
+/// Attributes that apply to listed sections
+pub const TAG_SECTION: u32 = 2;
+/// Attributes that apply to listed symbols
+pub const TAG_SYMBOL: u32 = 3;
+/// Raw CPU name given to the toolchain (string)
+pub const TAG_CPU_RAW_NAME: u32 = 4;
+/// Canonical CPU name (string)
+pub const TAG_CPU_NAME: u32 = 5;
+/// Architecture version (e.g. 10 for v7, 14 for v8)
+pub const TAG_CPU_ARCH: u32 = 6;
+/// Architecture profile: `'A'`, `'R'`, `'M'`, `'S'`, or 0
+pub const TAG_CPU_ARCH_PROFILE: u32 = 7;
+pub const TAG_ARM_ISA_USE: u32 = 8;
+pub const TAG_THUMB_ISA_USE: u32 = 9;
+/// Floating-point architecture (0 for none)
+pub const TAG_FP_ARCH: u32 = 10;
+pub const TAG_ADVANCED_SIMD_ARCH: u32 = 12;
+pub const TAG_ABI_PCS_CONFIG: u32 = 13;
+pub const TAG_ABI_PCS_WCHAR_T: u32 = 18;
+pub const TAG_ABI_FP_NUMBER_MODEL: u32 = 23;
+pub const TAG_ABI_ENUM_SIZE: u32 = 26;
+/// Floating-point argument passing: 0 base (soft) PCS, 1 VFP registers,
+/// 2 toolchain-specific, 3 compatible with both
+pub const TAG_ABI_VFP_ARGS: u32 = 28;
+/// Flag and vendor name this file is compatible with (integer and string)
+pub const TAG_COMPATIBILITY: u32 = 32;
+pub const TAG_CPU_UNALIGNED_ACCESS: u32 = 34;
+/// Version of the ABI the file conforms to (string)
+pub const TAG_CONFORMANCE: u32 = 67;
+
+/// `Tag_CPU_arch` values (those for M-profile cores, and the first v8)
+pub const CPU_ARCH_V6_M: u64 = 11;
+pub const CPU_ARCH_V6S_M: u64 = 12;
+pub const CPU_ARCH_V7E_M: u64 = 13;
+/// ARMv8-A; every later value is also v8 or newer
+pub const CPU_ARCH_V8: u64 = 14;
+pub const CPU_ARCH_V8_M_BASE: u64 = 16;
+pub const CPU_ARCH_V8_M_MAIN: u64 = 17;
+pub const CPU_ARCH_V8_1_M_MAIN: u64 = 21;
+
+/// Value of an ARM build attribute
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AttrValue {
+    Int(u64),
+    Str(String),
+    /// `Tag_compatibility`, which has both
+    IntStr(u64, String),
+}
+
+/// MIPS "noreorder" assembler directive was used
+pub const EF_MIPS_NOREORDER: u32 = 0x00000001;
+/// Position-independent code
+pub const EF_MIPS_PIC: u32 = 0x00000002;
+/// Follows the PIC calling sequence
+pub const EF_MIPS_CPIC: u32 = 0x00000004;
+/// N32 ABI (in a 32-bit file)
+pub const EF_MIPS_ABI2: u32 = 0x00000020;
+/// Mask for the 32-bit ABI variant
+pub const EF_MIPS_ABI: u32 = 0x0000f000;
+pub const E_MIPS_ABI_O32: u32 = 0x00001000;
+pub const E_MIPS_ABI_O64: u32 = 0x00002000;
+pub const E_MIPS_ABI_EABI32: u32 = 0x00003000;
+pub const E_MIPS_ABI_EABI64: u32 = 0x00004000;
+/// Mask for the ISA level
+pub const EF_MIPS_ARCH: u32 = 0xf0000000;
+
+/// MIPS ISA level from `e_flags`
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct MipsIsa(pub u32);
+pub const EF_MIPS_ARCH_1: MipsIsa = MipsIsa(0x00000000);
+pub const EF_MIPS_ARCH_2: MipsIsa = MipsIsa(0x10000000);
+pub const EF_MIPS_ARCH_3: MipsIsa = MipsIsa(0x20000000);
+pub const EF_MIPS_ARCH_4: MipsIsa = MipsIsa(0x30000000);
+pub const EF_MIPS_ARCH_5: MipsIsa = MipsIsa(0x40000000);
+pub const EF_MIPS_ARCH_32: MipsIsa = MipsIsa(0x50000000);
+pub const EF_MIPS_ARCH_64: MipsIsa = MipsIsa(0x60000000);
+pub const EF_MIPS_ARCH_32R2: MipsIsa = MipsIsa(0x70000000);
+pub const EF_MIPS_ARCH_64R2: MipsIsa = MipsIsa(0x80000000);
+pub const EF_MIPS_ARCH_32R6: MipsIsa = MipsIsa(0x90000000);
+pub const EF_MIPS_ARCH_64R6: MipsIsa = MipsIsa(0xa0000000);
+
+impl fmt::Debug for MipsIsa {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:#x}", self.0)
+    }
+}
+
+impl fmt::Display for MipsIsa {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let str = match *self {
+            EF_MIPS_ARCH_1 => "mips1",
+            EF_MIPS_ARCH_2 => "mips2",
+            EF_MIPS_ARCH_3 => "mips3",
+            EF_MIPS_ARCH_4 => "mips4",
+            EF_MIPS_ARCH_5 => "mips5",
+            EF_MIPS_ARCH_32 => "mips32",
+            EF_MIPS_ARCH_64 => "mips64",
+            EF_MIPS_ARCH_32R2 => "mips32r2",
+            EF_MIPS_ARCH_64R2 => "mips64r2",
+            EF_MIPS_ARCH_32R6 => "mips32r6",
+            EF_MIPS_ARCH_64R6 => "mips64r6",
             _ => "unknown",
         };
         write!(f, "{}", str)
     }
 }
 
+/// MIPS calling convention
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MipsAbi {
+    O32,
+    N32,
+    N64,
+    O64,
+    Eabi32,
+    Eabi64,
+}
+
+/// MIPS `e_flags`, decoded
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MipsFlags {
+    pub abi: MipsAbi,
+    pub isa: MipsIsa,
+    pub pic: bool,
+    pub cpic: bool,
+    pub noreorder: bool,
+}
+
 /// ELF object file type (object, executable)
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub struct Type(pub u16);
@@ -447,12 +664,12 @@ impl fmt::Debug for Type {
 impl fmt::Display for Type {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let str = match *self {
-            ET_NONE => "invalid",
-            ET_REL => "relocatable",
-            ET_EXEC => "executable",
-            ET_DYN => "shared object",
-            ET_CORE => "core",
-            _ => "unknown",
+            ET_NONE => "NONE (None)",
+            ET_REL => "REL (Relocatable file)",
+            ET_EXEC => "EXEC (Executable file)",
+            ET_DYN => "DYN (Shared object file)",
+            ET_CORE => "CORE (Core file)",
+            _ => return write!(f, "Unknown ({:#x})", self.0),
         };
         write!(f, "{}", str)
     }
@@ -509,6 +726,8 @@ pub const SHT_GNU_VERDEF: SectionType = SectionType(0x6ffffffd);
 pub const SHT_GNU_VERNEED: SectionType = SectionType(0x6ffffffe);
 /// Version symbol table
 pub const SHT_GNU_VERSYM: SectionType = SectionType(0x6fffffff);
+/// ARM build attributes (`.ARM.attributes`)
+pub const SHT_ARM_ATTRIBUTES: SectionType = SectionType(0x70000003);
 
 impl fmt::Debug for SectionType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -543,9 +762,14 @@ impl fmt::Display for SectionType {
             SHT_GNU_VERDEF => "SHT_GNU_VERDEF",
             SHT_GNU_VERNEED => "SHT_GNU_VERNEED",
             SHT_GNU_VERSYM => "SHT_GNU_VERSYM",
-            _ => "unknown",
+            SHT_ARM_ATTRIBUTES => "SHT_ARM_ATTRIBUTES",
+            _ => {
+                let mut unknown = String::new();
+                try!(fmt::Write::write_fmt(&mut unknown, format_args!("Unknown ({:#x})", self.0)));
+                return f.pad(&unknown);
+            },
         };
-        write!(f, "{}", str)
+        f.pad(str)
     }
 }
 
@@ -573,21 +797,62 @@ pub const SHF_OS_NONCONFORMING: SectionFlag = SectionFlag(1 << 8);
 pub const SHF_GROUP: SectionFlag = SectionFlag(1 << 9);
 /// Section holds thread-local data
 pub const SHF_TLS: SectionFlag = SectionFlag(1 << 10);
+/// Section data is compressed, behind an `Elf_Chdr` header
+pub const SHF_COMPRESSED: SectionFlag = SectionFlag(1 << 11);
 /// Special ordering requirement (Solaris)
 pub const SHF_ORDERED: SectionFlag = SectionFlag(1 << 30);
 /// Section is excluded unless referenced or allocared (Solaris)
 pub const SHF_EXCLUDE: SectionFlag = SectionFlag(1 << 31);
 
+impl SectionFlag {
+    /// Whether every bit of `flag` is set
+    pub fn contains(&self, flag: SectionFlag) -> bool {
+        self.0 & flag.0 == flag.0
+    }
+}
+
 impl fmt::Debug for SectionFlag {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{:#x}", self.0)
     }
 }
+
+/// Renders the flags as `readelf -S` style letters (e.g. `WAX`)
 impl fmt::Display for SectionFlag {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:#x}", self.0)
+        let letters = [
+            (SHF_WRITE, 'W'),
+            (SHF_ALLOC, 'A'),
+            (SHF_EXECINSTR, 'X'),
+            (SHF_MERGE, 'M'),
+            (SHF_STRINGS, 'S'),
+            (SHF_INFO_LINK, 'I'),
+            (SHF_LINK_ORDER, 'L'),
+            (SHF_OS_NONCONFORMING, 'O'),
+            (SHF_GROUP, 'G'),
+            (SHF_TLS, 'T'),
+            (SHF_COMPRESSED, 'C'),
+            (SHF_ORDERED, 'o'),
+            (SHF_EXCLUDE, 'E'),
+        ];
+        let mut buf = [0u8; 13];
+        let mut len = 0;
+        for &(flag, letter) in letters.iter() {
+            if self.contains(flag) {
+                buf[len] = letter as u8;
+                len += 1;
+            }
+        }
+        f.pad(str::from_utf8(&buf[..len]).unwrap_or(""))
     }
 }
+/// Header of a GNU-style `.zdebug_*` compressed section, followed by a 64-bit big-endian size
+pub static ZDEBUG_MAGIC: &[u8; 4] = b"ZLIB";
+/// zlib/deflate compressed section data
+pub const ELFCOMPRESS_ZLIB: u32 = 1;
+/// Zstandard compressed section data
+pub const ELFCOMPRESS_ZSTD: u32 = 2;
+
 /// ELF program header type
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub struct ProgType(pub u32);
@@ -681,23 +946,354 @@ impl fmt::Display for ProgFlag {
     }
 }
 
+/// Undefined section
+pub const SHN_UNDEF: u16 = 0;
+/// Start of reserved indices
+pub const SHN_LORESERVE: u16 = 0xff00;
+/// Associated symbol is absolute
+pub const SHN_ABS: u16 = 0xfff1;
+/// Associated symbol is common
+pub const SHN_COMMON: u16 = 0xfff2;
+/// Index is in extra table
+pub const SHN_XINDEX: u16 = 0xffff;
+
+/// Program header count is in section 0's `sh_info`
+pub const PN_XNUM: u16 = 0xffff;
+
+/// Versym index of a local symbol
+pub const VER_NDX_LOCAL: u16 = 0;
+/// Versym index of an unversioned global symbol
+pub const VER_NDX_GLOBAL: u16 = 1;
+/// Versym bit marking a symbol as hidden (not the default version)
+pub const VERSYM_HIDDEN: u16 = 0x8000;
+/// Versym mask for the version index
+pub const VERSYM_VERSION: u16 = 0x7fff;
+/// Verdef flag of the entry naming the file itself
+pub const VER_FLG_BASE: u16 = 0x1;
+
+/// ELF symbol binding
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SymbolBind(pub u8);
+/// Local symbol
+pub const STB_LOCAL: SymbolBind = SymbolBind(0);
+/// Global symbol
+pub const STB_GLOBAL: SymbolBind = SymbolBind(1);
+/// Weak symbol
+pub const STB_WEAK: SymbolBind = SymbolBind(2);
+/// Unique symbol (GNU extension)
+pub const STB_GNU_UNIQUE: SymbolBind = SymbolBind(10);
+
+impl fmt::Debug for SymbolBind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:#x}", self.0)
+    }
+}
+
+impl fmt::Display for SymbolBind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let str = match *self {
+            STB_LOCAL => "LOCAL",
+            STB_GLOBAL => "GLOBAL",
+            STB_WEAK => "WEAK",
+            STB_GNU_UNIQUE => "UNIQUE",
+            _ => "unknown",
+        };
+        write!(f, "{}", str)
+    }
+}
+
+/// ELF symbol type
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SymbolType(pub u8);
+/// Symbol type is unspecified
+pub const STT_NOTYPE: SymbolType = SymbolType(0);
+/// Symbol is a data object
+pub const STT_OBJECT: SymbolType = SymbolType(1);
+/// Symbol is a code object
+pub const STT_FUNC: SymbolType = SymbolType(2);
+/// Symbol associated with a section
+pub const STT_SECTION: SymbolType = SymbolType(3);
+/// Symbol's name is file name
+pub const STT_FILE: SymbolType = SymbolType(4);
+/// Symbol is a common data object
+pub const STT_COMMON: SymbolType = SymbolType(5);
+/// Symbol is thread-local data object
+pub const STT_TLS: SymbolType = SymbolType(6);
+/// Symbol is an indirect code object (GNU extension)
+pub const STT_GNU_IFUNC: SymbolType = SymbolType(10);
+
+impl fmt::Debug for SymbolType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:#x}", self.0)
+    }
+}
+
+impl fmt::Display for SymbolType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let str = match *self {
+            STT_NOTYPE => "NOTYPE",
+            STT_OBJECT => "OBJECT",
+            STT_FUNC => "FUNC",
+            STT_SECTION => "SECTION",
+            STT_FILE => "FILE",
+            STT_COMMON => "COMMON",
+            STT_TLS => "TLS",
+            STT_GNU_IFUNC => "IFUNC",
+            _ => "unknown",
+        };
+        write!(f, "{}", str)
+    }
+}
+
+/// ELF symbol visibility
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SymbolVis(pub u8);
+/// Default symbol visibility rules
+pub const STV_DEFAULT: SymbolVis = SymbolVis(0);
+/// Processor specific hidden class
+pub const STV_INTERNAL: SymbolVis = SymbolVis(1);
+/// Symbol unavailable in other modules
+pub const STV_HIDDEN: SymbolVis = SymbolVis(2);
+/// Not preemptible, not exported
+pub const STV_PROTECTED: SymbolVis = SymbolVis(3);
+
+impl fmt::Debug for SymbolVis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:#x}", self.0)
+    }
+}
+
+impl fmt::Display for SymbolVis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let str = match *self {
+            STV_DEFAULT => "DEFAULT",
+            STV_INTERNAL => "INTERNAL",
+            STV_HIDDEN => "HIDDEN",
+            STV_PROTECTED => "PROTECTED",
+            _ => "unknown",
+        };
+        write!(f, "{}", str)
+    }
+}
+
+/// ELF dynamic section entry tag
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct DynTag(pub u64);
+/// Marks end of dynamic section
+pub const DT_NULL: DynTag = DynTag(0);
+/// Name of needed library
+pub const DT_NEEDED: DynTag = DynTag(1);
+/// Size in bytes of PLT relocs
+pub const DT_PLTRELSZ: DynTag = DynTag(2);
+/// Processor defined value
+pub const DT_PLTGOT: DynTag = DynTag(3);
+/// Address of symbol hash table
+pub const DT_HASH: DynTag = DynTag(4);
+/// Address of string table
+pub const DT_STRTAB: DynTag = DynTag(5);
+/// Address of symbol table
+pub const DT_SYMTAB: DynTag = DynTag(6);
+/// Address of Rela relocs
+pub const DT_RELA: DynTag = DynTag(7);
+/// Total size of Rela relocs
+pub const DT_RELASZ: DynTag = DynTag(8);
+/// Size of one Rela reloc
+pub const DT_RELAENT: DynTag = DynTag(9);
+/// Size of string table
+pub const DT_STRSZ: DynTag = DynTag(10);
+/// Size of one symbol table entry
+pub const DT_SYMENT: DynTag = DynTag(11);
+/// Address of init function
+pub const DT_INIT: DynTag = DynTag(12);
+/// Address of termination function
+pub const DT_FINI: DynTag = DynTag(13);
+/// Name of shared object
+pub const DT_SONAME: DynTag = DynTag(14);
+/// Library search path (deprecated)
+pub const DT_RPATH: DynTag = DynTag(15);
+/// Start symbol search here
+pub const DT_SYMBOLIC: DynTag = DynTag(16);
+/// Address of Rel relocs
+pub const DT_REL: DynTag = DynTag(17);
+/// Total size of Rel relocs
+pub const DT_RELSZ: DynTag = DynTag(18);
+/// Size of one Rel reloc
+pub const DT_RELENT: DynTag = DynTag(19);
+/// Type of reloc in PLT
+pub const DT_PLTREL: DynTag = DynTag(20);
+/// For debugging; unspecified
+pub const DT_DEBUG: DynTag = DynTag(21);
+/// Reloc might modify .text
+pub const DT_TEXTREL: DynTag = DynTag(22);
+/// Address of PLT relocs
+pub const DT_JMPREL: DynTag = DynTag(23);
+/// Process relocations of object
+pub const DT_BIND_NOW: DynTag = DynTag(24);
+/// Array with addresses of init fct
+pub const DT_INIT_ARRAY: DynTag = DynTag(25);
+/// Array with addresses of fini fct
+pub const DT_FINI_ARRAY: DynTag = DynTag(26);
+/// Size in bytes of DT_INIT_ARRAY
+pub const DT_INIT_ARRAYSZ: DynTag = DynTag(27);
+/// Size in bytes of DT_FINI_ARRAY
+pub const DT_FINI_ARRAYSZ: DynTag = DynTag(28);
+/// Library search path
+pub const DT_RUNPATH: DynTag = DynTag(29);
+/// Flags for the object being loaded
+pub const DT_FLAGS: DynTag = DynTag(30);
+/// Array with addresses of preinit fct
+pub const DT_PREINIT_ARRAY: DynTag = DynTag(32);
+/// Size in bytes of DT_PREINIT_ARRAY
+pub const DT_PREINIT_ARRAYSZ: DynTag = DynTag(33);
+/// GNU-style hash table
+pub const DT_GNU_HASH: DynTag = DynTag(0x6ffffef5);
+/// Address of version symbol table
+pub const DT_VERSYM: DynTag = DynTag(0x6ffffff0);
+/// Number of relative Rela relocs
+pub const DT_RELACOUNT: DynTag = DynTag(0x6ffffff9);
+/// Number of relative Rel relocs
+pub const DT_RELCOUNT: DynTag = DynTag(0x6ffffffa);
+/// State flags
+pub const DT_FLAGS_1: DynTag = DynTag(0x6ffffffb);
+/// Address of version definition table
+pub const DT_VERDEF: DynTag = DynTag(0x6ffffffc);
+/// Number of version definitions
+pub const DT_VERDEFNUM: DynTag = DynTag(0x6ffffffd);
+/// Address of table with needed versions
+pub const DT_VERNEED: DynTag = DynTag(0x6ffffffe);
+/// Number of needed versions
+pub const DT_VERNEEDNUM: DynTag = DynTag(0x6fffffff);
+
+impl fmt::Debug for DynTag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:#x}", self.0)
+    }
+}
+
+impl fmt::Display for DynTag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let str = match *self {
+            DT_NULL => "DT_NULL",
+            DT_NEEDED => "DT_NEEDED",
+            DT_PLTRELSZ => "DT_PLTRELSZ",
+            DT_PLTGOT => "DT_PLTGOT",
+            DT_HASH => "DT_HASH",
+            DT_STRTAB => "DT_STRTAB",
+            DT_SYMTAB => "DT_SYMTAB",
+            DT_RELA => "DT_RELA",
+            DT_RELASZ => "DT_RELASZ",
+            DT_RELAENT => "DT_RELAENT",
+            DT_STRSZ => "DT_STRSZ",
+            DT_SYMENT => "DT_SYMENT",
+            DT_INIT => "DT_INIT",
+            DT_FINI => "DT_FINI",
+            DT_SONAME => "DT_SONAME",
+            DT_RPATH => "DT_RPATH",
+            DT_SYMBOLIC => "DT_SYMBOLIC",
+            DT_REL => "DT_REL",
+            DT_RELSZ => "DT_RELSZ",
+            DT_RELENT => "DT_RELENT",
+            DT_PLTREL => "DT_PLTREL",
+            DT_DEBUG => "DT_DEBUG",
+            DT_TEXTREL => "DT_TEXTREL",
+            DT_JMPREL => "DT_JMPREL",
+            DT_BIND_NOW => "DT_BIND_NOW",
+            DT_INIT_ARRAY => "DT_INIT_ARRAY",
+            DT_FINI_ARRAY => "DT_FINI_ARRAY",
+            DT_INIT_ARRAYSZ => "DT_INIT_ARRAYSZ",
+            DT_FINI_ARRAYSZ => "DT_FINI_ARRAYSZ",
+            DT_RUNPATH => "DT_RUNPATH",
+            DT_FLAGS => "DT_FLAGS",
+            DT_PREINIT_ARRAY => "DT_PREINIT_ARRAY",
+            DT_PREINIT_ARRAYSZ => "DT_PREINIT_ARRAYSZ",
+            DT_GNU_HASH => "DT_GNU_HASH",
+            DT_VERSYM => "DT_VERSYM",
+            DT_RELACOUNT => "DT_RELACOUNT",
+            DT_RELCOUNT => "DT_RELCOUNT",
+            DT_FLAGS_1 => "DT_FLAGS_1",
+            DT_VERDEF => "DT_VERDEF",
+            DT_VERDEFNUM => "DT_VERDEFNUM",
+            DT_VERNEED => "DT_VERNEED",
+            DT_VERNEEDNUM => "DT_VERNEEDNUM",
+            _ => "unknown",
+        };
+        write!(f, "{}", str)
+    }
+}
+
+/// `DT_FLAGS_1`: set RTLD_NOW for this object
+pub const DF_1_NOW: u64 = 0x1;
+/// `DT_FLAGS_1`: object is a position-independent executable
+pub const DF_1_PIE: u64 = 0x08000000;
+
+/// GNU ABI tag note
+pub const NT_GNU_ABI_TAG: u32 = 1;
+/// GNU hardware capabilities note
+pub const NT_GNU_HWCAP: u32 = 2;
+/// GNU build ID note
+pub const NT_GNU_BUILD_ID: u32 = 3;
+/// GNU gold linker version note
+pub const NT_GNU_GOLD_VERSION: u32 = 4;
+/// GNU program property note
+pub const NT_GNU_PROPERTY_TYPE_0: u32 = 5;
+
+/// Core file thread status note (owner `CORE`), `struct elf_prstatus`
+pub const NT_PRSTATUS: u32 = 1;
+/// Core file process info note (owner `CORE`), `struct elf_prpsinfo`
+pub const NT_PRPSINFO: u32 = 3;
+/// Core file auxiliary vector note (owner `CORE`)
+pub const NT_AUXV: u32 = 6;
+
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct FileHeader {
     pub class: Class,
     pub data: Data,
+    /// `e_ident[EI_VERSION]`, which should match `version`
+    pub ident_version: u8,
     pub version: Version,
     pub os_abi: OsAbi,
     pub abi_version: u8,
     pub elf_type: Type,
     pub machine: Machine,
     pub entrypoint: u64,
+    /// Processor-specific `e_flags`; see `File::arm_flags` and
+    /// `File::mips_flags` for decoded forms
+    pub flags: u32,
 }
 
 impl fmt::Display for FileHeader {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        writeln!(f, "ELF header for {} {} ELF {} for {} {}", self.class, self.data, self.elf_type, self.os_abi, self.machine)
+        writeln!(f, "ELF header for {} {} ELF, type {}, for {} {}", self.class, self.data, self.elf_type, self.os_abi, self.machine)
     }
 }
 
+/// Something unusual about the file that didn't stop it being parsed
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum Warning {
+    /// `e_version` is not `EV_CURRENT`
+    BadVersion(Version),
+    /// `e_phentsize` is larger than a program header for the file's class
+    PhentsizeMismatch { expected: u64, found: u64 },
+    /// `e_shentsize` is larger than a section header for the file's class
+    ShentsizeMismatch { expected: u64, found: u64 },
+    /// A `.gnu.version` entry names a version no verdef or verneed entry
+    /// defines, so the symbol is left unversioned
+    UnknownSymbolVersion { symbol: usize, version: u16 },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Warning::BadVersion(v) => write!(f, "unexpected ELF version {}", v.0),
+            Warning::PhentsizeMismatch { expected, found } => write!(f, "program header entry size {} (expected {})", found, expected),
+            Warning::ShentsizeMismatch { expected, found } => write!(f, "section header entry size {} (expected {})", found, expected),
+            Warning::UnknownSymbolVersion { symbol, version } => write!(f, "dynamic symbol {} has undefined version index {}", symbol, version),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct SectionHeader {
     pub name: String,
     pub shtype: SectionType,
@@ -724,6 +1320,7 @@ impl fmt::Display for SectionHeader {
     }
 }
 
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct ProgramHeader {
     pub progtype: ProgType,
     pub offset: u64,
@@ -734,3 +1331,619 @@ pub struct ProgramHeader {
     pub flags: ProgFlag,
     pub align: u64,
 }
+
+impl fmt::Display for ProgramHeader {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Segment type: {} flags: {} offset: {:#06x} vaddr: {:#010x} paddr: {:#010x} filesz: {:#06x} memsz: {:#06x} align: {}",
+               self.progtype, self.flags, self.offset, self.vaddr, self.paddr, self.filesz, self.memsz, self.align)
+    }
+}
+
+/// A way in which a `PT_LOAD` segment can't be mapped as described, found
+/// by `File::verify_loadable`; `segment` is the program header index
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum LoadError {
+    /// The file data extends past the end of the file
+    Truncated { segment: usize, end: u64, file_len: u64 },
+    /// More bytes come from the file than the segment occupies in memory
+    FileszExceedsMemsz { segment: usize, filesz: u64, memsz: u64 },
+    /// `p_align` is neither 0, 1 nor a power of two
+    BadAlign { segment: usize, align: u64 },
+    /// `p_vaddr` and `p_offset` differ modulo `p_align`
+    Misaligned { segment: usize, vaddr: u64, offset: u64, align: u64 },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LoadError::Truncated { segment, end, file_len } =>
+                write!(f, "segment {} ends at {:#x}, past the end of the file ({:#x})", segment, end, file_len),
+            LoadError::FileszExceedsMemsz { segment, filesz, memsz } =>
+                write!(f, "segment {} has filesz {:#x} larger than memsz {:#x}", segment, filesz, memsz),
+            LoadError::BadAlign { segment, align } =>
+                write!(f, "segment {} has alignment {:#x}, not a power of two", segment, align),
+            LoadError::Misaligned { segment, vaddr, offset, align } =>
+                write!(f, "segment {} vaddr {:#x} and offset {:#x} differ modulo {:#x}", segment, vaddr, offset, align),
+        }
+    }
+}
+
+/// Thread-local storage template, from the `PT_TLS` segment
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct TlsInfo {
+    /// Virtual address of the initialization image (`.tdata`)
+    pub addr: u64,
+    /// File offset of the initialization image
+    pub offset: u64,
+    /// Size of the initialization image
+    pub filesz: u64,
+    /// Size of each thread's block, including the zero-filled part
+    pub memsz: u64,
+    /// Zero-filled part of the block (`.tbss`), i.e. `memsz - filesz`
+    pub bss_size: u64,
+    pub align: u64,
+}
+
+/// Group flag: keep only one copy of the group when linking
+pub const GRP_COMDAT: u32 = 0x1;
+
+/// A section group (`SHT_GROUP`), whose members are kept or discarded by
+/// the linker together
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct SectionGroup {
+    /// Index of the `SHT_GROUP` section itself
+    pub section: usize,
+    /// Symbol table section holding the signature symbol (`sh_link`)
+    pub symtab: u32,
+    /// Index of the signature symbol in that table (`sh_info`)
+    pub symbol: u32,
+    /// Name of the signature symbol, which identifies the group; for
+    /// section symbols this is the section's name
+    pub signature: Option<String>,
+    /// Group flags word, see `GRP_COMDAT`
+    pub flags: u32,
+    /// Indices of the member sections
+    pub members: Vec<u32>,
+}
+
+impl SectionGroup {
+    pub fn is_comdat(&self) -> bool {
+        self.flags & GRP_COMDAT != 0
+    }
+}
+
+/// A suspicious feature of a file's section or segment layout
+///
+/// Ranges are half-open, `[start, end)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum Anomaly {
+    /// Two sections share the file bytes `start..end`
+    SectionOverlap { first: usize, second: usize, start: u64, end: u64 },
+    /// Two PT_LOAD segments share the virtual addresses `start..end`
+    SegmentOverlap { first: usize, second: usize, start: u64, end: u64 },
+    /// A PT_LOAD segment has a lower address than the one before it
+    SegmentOrder { segment: usize, previous: usize },
+    /// File bytes `start..end` between two sections belong to neither, and
+    /// are more than alignment padding
+    Gap { start: u64, end: u64 },
+    /// An allocated section at addresses `start..end` lies in no PT_LOAD segment
+    OutsideSegments { section: usize, start: u64, end: u64 },
+    /// An allocated section has a size of 0
+    EmptyAlloc { section: usize, addr: u64 },
+}
+
+impl fmt::Display for Anomaly {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Anomaly::SectionOverlap { first, second, start, end } =>
+                write!(f, "sections {} and {} overlap at offsets {:#x}..{:#x}", first, second, start, end),
+            Anomaly::SegmentOverlap { first, second, start, end } =>
+                write!(f, "segments {} and {} overlap at addresses {:#x}..{:#x}", first, second, start, end),
+            Anomaly::SegmentOrder { segment, previous } =>
+                write!(f, "segment {} is mapped below segment {}", segment, previous),
+            Anomaly::Gap { start, end } =>
+                write!(f, "unused bytes at offsets {:#x}..{:#x}", start, end),
+            Anomaly::OutsideSegments { section, start, end } =>
+                write!(f, "section {} at addresses {:#x}..{:#x} is outside every segment", section, start, end),
+            Anomaly::EmptyAlloc { section, addr } =>
+                write!(f, "allocated section {} at {:#x} is empty", section, addr),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Symbol {
+    pub name: String,
+    pub value: u64,
+    pub size: u64,
+    pub bind: SymbolBind,
+    pub sym_type: SymbolType,
+    pub vis: SymbolVis,
+    pub shndx: u16,
+    /// GNU symbol version (e.g. `GLIBC_2.14`), for dynamic symbols of files
+    /// with a `.gnu.version` section
+    pub version: Option<String>,
+    /// The versym entry has `VERSYM_HIDDEN` set, i.e. this is not the
+    /// default version of the symbol
+    pub version_hidden: bool,
+}
+
+impl Symbol {
+    /// The symbol name with Rust or C++ mangling undone
+    #[cfg(feature = "demangle")]
+    pub fn demangled(&self) -> String {
+        ::demangle(&self.name)
+    }
+    /// The demangled name with the version suffix of `versioned_name`
+    #[cfg(feature = "demangle")]
+    pub fn demangled_versioned(&self) -> String {
+        self.with_version(::demangle(&self.name))
+    }
+    /// The name with its GNU version appended as binutils shows it:
+    /// `name@@VERSION` for the default version of a defined symbol, and
+    /// `name@VERSION` for a hidden version or an undefined reference. The
+    /// symbols naming a version definition itself are left bare.
+    pub fn versioned_name(&self) -> String {
+        self.with_version(self.name.clone())
+    }
+    fn with_version(&self, mut name: String) -> String {
+        match self.version {
+            Some(ref version) if *version != self.name => {
+                if self.version_hidden || self.shndx == SHN_UNDEF {
+                    name.push('@');
+                } else {
+                    name.push_str("@@");
+                }
+                name.push_str(version);
+            },
+            _ => {},
+        }
+        name
+    }
+}
+
+/// The alternate form (`{:#}`) shows the name with its version suffix
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = if f.alternate() { self.versioned_name() } else { self.name.clone() };
+        writeln!(f, "Symbol '{}': value: {:#010x} size: {} type: {} bind: {} vis: {} shndx: {}",
+               name, self.value, self.size, self.sym_type, self.bind, self.vis, self.shndx)
+    }
+}
+
+/// x86-64 PLT entry (GOT slot filled in by the dynamic linker)
+pub const R_X86_64_JUMP_SLOT: u32 = 7;
+/// AArch64 PLT entry (GOT slot filled in by the dynamic linker)
+pub const R_AARCH64_JUMP_SLOT: u32 = 1026;
+
+/// Name of a relocation type on the given machine, e.g. `R_X86_64_GLOB_DAT`
+///
+/// Covers x86-64, AArch64 (LP64) and i386; `None` for other machines or
+/// unassigned numbers.
+pub fn reloc_type_name(machine: Machine, rel_type: u32) -> Option<&'static str> {
+    let name = match machine {
+        EM_X86_64 => match rel_type {
+            0 => "R_X86_64_NONE",
+            1 => "R_X86_64_64",
+            2 => "R_X86_64_PC32",
+            3 => "R_X86_64_GOT32",
+            4 => "R_X86_64_PLT32",
+            5 => "R_X86_64_COPY",
+            6 => "R_X86_64_GLOB_DAT",
+            7 => "R_X86_64_JUMP_SLOT",
+            8 => "R_X86_64_RELATIVE",
+            9 => "R_X86_64_GOTPCREL",
+            10 => "R_X86_64_32",
+            11 => "R_X86_64_32S",
+            12 => "R_X86_64_16",
+            13 => "R_X86_64_PC16",
+            14 => "R_X86_64_8",
+            15 => "R_X86_64_PC8",
+            16 => "R_X86_64_DTPMOD64",
+            17 => "R_X86_64_DTPOFF64",
+            18 => "R_X86_64_TPOFF64",
+            19 => "R_X86_64_TLSGD",
+            20 => "R_X86_64_TLSLD",
+            21 => "R_X86_64_DTPOFF32",
+            22 => "R_X86_64_GOTTPOFF",
+            23 => "R_X86_64_TPOFF32",
+            24 => "R_X86_64_PC64",
+            25 => "R_X86_64_GOTOFF64",
+            26 => "R_X86_64_GOTPC32",
+            27 => "R_X86_64_GOT64",
+            28 => "R_X86_64_GOTPCREL64",
+            29 => "R_X86_64_GOTPC64",
+            30 => "R_X86_64_GOTPLT64",
+            31 => "R_X86_64_PLTOFF64",
+            32 => "R_X86_64_SIZE32",
+            33 => "R_X86_64_SIZE64",
+            34 => "R_X86_64_GOTPC32_TLSDESC",
+            35 => "R_X86_64_TLSDESC_CALL",
+            36 => "R_X86_64_TLSDESC",
+            37 => "R_X86_64_IRELATIVE",
+            38 => "R_X86_64_RELATIVE64",
+            41 => "R_X86_64_GOTPCRELX",
+            42 => "R_X86_64_REX_GOTPCRELX",
+            _ => return None,
+        },
+        EM_AARCH64 => match rel_type {
+            0 => "R_AARCH64_NONE",
+            257 => "R_AARCH64_ABS64",
+            258 => "R_AARCH64_ABS32",
+            259 => "R_AARCH64_ABS16",
+            260 => "R_AARCH64_PREL64",
+            261 => "R_AARCH64_PREL32",
+            262 => "R_AARCH64_PREL16",
+            263 => "R_AARCH64_MOVW_UABS_G0",
+            264 => "R_AARCH64_MOVW_UABS_G0_NC",
+            265 => "R_AARCH64_MOVW_UABS_G1",
+            266 => "R_AARCH64_MOVW_UABS_G1_NC",
+            267 => "R_AARCH64_MOVW_UABS_G2",
+            268 => "R_AARCH64_MOVW_UABS_G2_NC",
+            269 => "R_AARCH64_MOVW_UABS_G3",
+            270 => "R_AARCH64_MOVW_SABS_G0",
+            271 => "R_AARCH64_MOVW_SABS_G1",
+            272 => "R_AARCH64_MOVW_SABS_G2",
+            273 => "R_AARCH64_LD_PREL_LO19",
+            274 => "R_AARCH64_ADR_PREL_LO21",
+            275 => "R_AARCH64_ADR_PREL_PG_HI21",
+            276 => "R_AARCH64_ADR_PREL_PG_HI21_NC",
+            277 => "R_AARCH64_ADD_ABS_LO12_NC",
+            278 => "R_AARCH64_LDST8_ABS_LO12_NC",
+            279 => "R_AARCH64_TSTBR14",
+            280 => "R_AARCH64_CONDBR19",
+            282 => "R_AARCH64_JUMP26",
+            283 => "R_AARCH64_CALL26",
+            284 => "R_AARCH64_LDST16_ABS_LO12_NC",
+            285 => "R_AARCH64_LDST32_ABS_LO12_NC",
+            286 => "R_AARCH64_LDST64_ABS_LO12_NC",
+            287 => "R_AARCH64_MOVW_PREL_G0",
+            288 => "R_AARCH64_MOVW_PREL_G0_NC",
+            289 => "R_AARCH64_MOVW_PREL_G1",
+            290 => "R_AARCH64_MOVW_PREL_G1_NC",
+            291 => "R_AARCH64_MOVW_PREL_G2",
+            292 => "R_AARCH64_MOVW_PREL_G2_NC",
+            293 => "R_AARCH64_MOVW_PREL_G3",
+            299 => "R_AARCH64_LDST128_ABS_LO12_NC",
+            300 => "R_AARCH64_MOVW_GOTOFF_G0",
+            301 => "R_AARCH64_MOVW_GOTOFF_G0_NC",
+            302 => "R_AARCH64_MOVW_GOTOFF_G1",
+            303 => "R_AARCH64_MOVW_GOTOFF_G1_NC",
+            304 => "R_AARCH64_MOVW_GOTOFF_G2",
+            305 => "R_AARCH64_MOVW_GOTOFF_G2_NC",
+            306 => "R_AARCH64_MOVW_GOTOFF_G3",
+            307 => "R_AARCH64_GOTREL64",
+            308 => "R_AARCH64_GOTREL32",
+            309 => "R_AARCH64_GOT_LD_PREL19",
+            310 => "R_AARCH64_LD64_GOTOFF_LO15",
+            311 => "R_AARCH64_ADR_GOT_PAGE",
+            312 => "R_AARCH64_LD64_GOT_LO12_NC",
+            313 => "R_AARCH64_LD64_GOTPAGE_LO15",
+            512 => "R_AARCH64_TLSGD_ADR_PREL21",
+            513 => "R_AARCH64_TLSGD_ADR_PAGE21",
+            514 => "R_AARCH64_TLSGD_ADD_LO12_NC",
+            515 => "R_AARCH64_TLSGD_MOVW_G1",
+            516 => "R_AARCH64_TLSGD_MOVW_G0_NC",
+            517 => "R_AARCH64_TLSLD_ADR_PREL21",
+            518 => "R_AARCH64_TLSLD_ADR_PAGE21",
+            519 => "R_AARCH64_TLSLD_ADD_LO12_NC",
+            520 => "R_AARCH64_TLSLD_MOVW_G1",
+            521 => "R_AARCH64_TLSLD_MOVW_G0_NC",
+            522 => "R_AARCH64_TLSLD_LD_PREL19",
+            523 => "R_AARCH64_TLSLD_MOVW_DTPREL_G2",
+            524 => "R_AARCH64_TLSLD_MOVW_DTPREL_G1",
+            525 => "R_AARCH64_TLSLD_MOVW_DTPREL_G1_NC",
+            526 => "R_AARCH64_TLSLD_MOVW_DTPREL_G0",
+            527 => "R_AARCH64_TLSLD_MOVW_DTPREL_G0_NC",
+            528 => "R_AARCH64_TLSLD_ADD_DTPREL_HI12",
+            529 => "R_AARCH64_TLSLD_ADD_DTPREL_LO12",
+            530 => "R_AARCH64_TLSLD_ADD_DTPREL_LO12_NC",
+            531 => "R_AARCH64_TLSLD_LDST8_DTPREL_LO12",
+            532 => "R_AARCH64_TLSLD_LDST8_DTPREL_LO12_NC",
+            533 => "R_AARCH64_TLSLD_LDST16_DTPREL_LO12",
+            534 => "R_AARCH64_TLSLD_LDST16_DTPREL_LO12_NC",
+            535 => "R_AARCH64_TLSLD_LDST32_DTPREL_LO12",
+            536 => "R_AARCH64_TLSLD_LDST32_DTPREL_LO12_NC",
+            537 => "R_AARCH64_TLSLD_LDST64_DTPREL_LO12",
+            538 => "R_AARCH64_TLSLD_LDST64_DTPREL_LO12_NC",
+            539 => "R_AARCH64_TLSIE_MOVW_GOTTPREL_G1",
+            540 => "R_AARCH64_TLSIE_MOVW_GOTTPREL_G0_NC",
+            541 => "R_AARCH64_TLSIE_ADR_GOTTPREL_PAGE21",
+            542 => "R_AARCH64_TLSIE_LD64_GOTTPREL_LO12_NC",
+            543 => "R_AARCH64_TLSIE_LD_GOTTPREL_PREL19",
+            544 => "R_AARCH64_TLSLE_MOVW_TPREL_G2",
+            545 => "R_AARCH64_TLSLE_MOVW_TPREL_G1",
+            546 => "R_AARCH64_TLSLE_MOVW_TPREL_G1_NC",
+            547 => "R_AARCH64_TLSLE_MOVW_TPREL_G0",
+            548 => "R_AARCH64_TLSLE_MOVW_TPREL_G0_NC",
+            549 => "R_AARCH64_TLSLE_ADD_TPREL_HI12",
+            550 => "R_AARCH64_TLSLE_ADD_TPREL_LO12",
+            551 => "R_AARCH64_TLSLE_ADD_TPREL_LO12_NC",
+            552 => "R_AARCH64_TLSLE_LDST8_TPREL_LO12",
+            553 => "R_AARCH64_TLSLE_LDST8_TPREL_LO12_NC",
+            554 => "R_AARCH64_TLSLE_LDST16_TPREL_LO12",
+            555 => "R_AARCH64_TLSLE_LDST16_TPREL_LO12_NC",
+            556 => "R_AARCH64_TLSLE_LDST32_TPREL_LO12",
+            557 => "R_AARCH64_TLSLE_LDST32_TPREL_LO12_NC",
+            558 => "R_AARCH64_TLSLE_LDST64_TPREL_LO12",
+            559 => "R_AARCH64_TLSLE_LDST64_TPREL_LO12_NC",
+            560 => "R_AARCH64_TLSDESC_LD_PREL19",
+            561 => "R_AARCH64_TLSDESC_ADR_PREL21",
+            562 => "R_AARCH64_TLSDESC_ADR_PAGE21",
+            563 => "R_AARCH64_TLSDESC_LD64_LO12",
+            564 => "R_AARCH64_TLSDESC_ADD_LO12",
+            565 => "R_AARCH64_TLSDESC_OFF_G1",
+            566 => "R_AARCH64_TLSDESC_OFF_G0_NC",
+            567 => "R_AARCH64_TLSDESC_LDR",
+            568 => "R_AARCH64_TLSDESC_ADD",
+            569 => "R_AARCH64_TLSDESC_CALL",
+            570 => "R_AARCH64_TLSLE_LDST128_TPREL_LO12",
+            571 => "R_AARCH64_TLSLE_LDST128_TPREL_LO12_NC",
+            572 => "R_AARCH64_TLSLD_LDST128_DTPREL_LO12",
+            573 => "R_AARCH64_TLSLD_LDST128_DTPREL_LO12_NC",
+            1024 => "R_AARCH64_COPY",
+            1025 => "R_AARCH64_GLOB_DAT",
+            1026 => "R_AARCH64_JUMP_SLOT",
+            1027 => "R_AARCH64_RELATIVE",
+            1028 => "R_AARCH64_TLS_DTPMOD",
+            1029 => "R_AARCH64_TLS_DTPREL",
+            1030 => "R_AARCH64_TLS_TPREL",
+            1031 => "R_AARCH64_TLSDESC",
+            1032 => "R_AARCH64_IRELATIVE",
+            _ => return None,
+        },
+        EM_386 => match rel_type {
+            0 => "R_386_NONE",
+            1 => "R_386_32",
+            2 => "R_386_PC32",
+            3 => "R_386_GOT32",
+            4 => "R_386_PLT32",
+            5 => "R_386_COPY",
+            6 => "R_386_GLOB_DAT",
+            7 => "R_386_JMP_SLOT",
+            8 => "R_386_RELATIVE",
+            9 => "R_386_GOTOFF",
+            10 => "R_386_GOTPC",
+            11 => "R_386_32PLT",
+            14 => "R_386_TLS_TPOFF",
+            15 => "R_386_TLS_IE",
+            16 => "R_386_TLS_GOTIE",
+            17 => "R_386_TLS_LE",
+            18 => "R_386_TLS_GD",
+            19 => "R_386_TLS_LDM",
+            20 => "R_386_16",
+            21 => "R_386_PC16",
+            22 => "R_386_8",
+            23 => "R_386_PC8",
+            24 => "R_386_TLS_GD_32",
+            25 => "R_386_TLS_GD_PUSH",
+            26 => "R_386_TLS_GD_CALL",
+            27 => "R_386_TLS_GD_POP",
+            28 => "R_386_TLS_LDM_32",
+            29 => "R_386_TLS_LDM_PUSH",
+            30 => "R_386_TLS_LDM_CALL",
+            31 => "R_386_TLS_LDM_POP",
+            32 => "R_386_TLS_LDO_32",
+            33 => "R_386_TLS_IE_32",
+            34 => "R_386_TLS_LE_32",
+            35 => "R_386_TLS_DTPMOD32",
+            36 => "R_386_TLS_DTPOFF32",
+            37 => "R_386_TLS_TPOFF32",
+            38 => "R_386_SIZE32",
+            39 => "R_386_TLS_GOTDESC",
+            40 => "R_386_TLS_DESC_CALL",
+            41 => "R_386_TLS_DESC",
+            42 => "R_386_IRELATIVE",
+            43 => "R_386_GOT32X",
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// A relocation with its type decoded and its symbol looked up
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ResolvedReloc {
+    /// Name of the SHT_REL/SHT_RELA section the entry came from
+    pub section: String,
+    pub offset: u64,
+    /// Type name from `reloc_type_name`, or the number in hex if unknown
+    pub rel_type: String,
+    /// `None` for relocations against symbol 0 (e.g. `R_X86_64_RELATIVE`);
+    /// section symbols are named after their section
+    pub symbol: Option<String>,
+    pub addend: i64,
+}
+
+/// Relocation entry without an explicit addend (SHT_REL)
+#[derive(Clone, Copy, Debug)]
+pub struct Rel {
+    pub offset: u64,
+    pub sym: u32,
+    pub rel_type: u32,
+}
+
+/// Relocation entry with an explicit addend (SHT_RELA)
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Rela {
+    pub offset: u64,
+    pub sym: u32,
+    pub rel_type: u32,
+    pub addend: i64,
+}
+
+impl From<Rel> for Rela {
+    fn from(rel: Rel) -> Rela {
+        Rela {
+            offset: rel.offset,
+            sym: rel.sym,
+            rel_type: rel.rel_type,
+            addend: 0,
+        }
+    }
+}
+
+impl fmt::Display for Rela {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Relocation offset: {:#010x} type: {} sym: {} addend: {}",
+               self.offset, self.rel_type, self.sym, self.addend)
+    }
+}
+
+/// Entry of the dynamic section
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct DynEntry {
+    pub tag: DynTag,
+    pub value: u64,
+}
+
+impl fmt::Display for DynEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Dynamic tag: {} value: {:#x}", self.tag, self.value)
+    }
+}
+
+/// Entry of a note section or segment
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Note {
+    /// Owner name, without the terminating NUL
+    pub name: String,
+    pub n_type: u32,
+    pub desc: Vec<u8>,
+}
+
+/// Process state recorded in an `ET_CORE` file's notes
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct CoreInfo {
+    /// One `NT_PRSTATUS` per thread, the first for the thread that received
+    /// the fatal signal
+    pub threads: Vec<PrStatus>,
+    /// `NT_PRPSINFO`, only decoded for 64-bit files
+    pub process: Option<PrPsInfo>,
+    /// `NT_AUXV` entries as `(AT_* type, value)`, without the final `AT_NULL`
+    pub auxv: Vec<(u64, u64)>,
+}
+
+/// Status of a thread in a core file (`struct elf_prstatus`)
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct PrStatus {
+    /// Signal number from the siginfo (`si_signo`)
+    pub signo: u32,
+    /// Current signal (`pr_cursig`)
+    pub cursig: u16,
+    pub pid: u32,
+    pub ppid: u32,
+    pub pgrp: u32,
+    pub sid: u32,
+    pub registers: Registers,
+}
+
+/// General purpose registers from `pr_reg`, whose layout is machine specific
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum Registers {
+    X86_64(X86_64Registers),
+    /// Undecoded `pr_reg` contents, in the file's byte order
+    Raw(Vec<u8>),
+}
+
+/// x86-64 `struct user_regs_struct`
+#[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct X86_64Registers {
+    pub r15: u64,
+    pub r14: u64,
+    pub r13: u64,
+    pub r12: u64,
+    pub rbp: u64,
+    pub rbx: u64,
+    pub r11: u64,
+    pub r10: u64,
+    pub r9: u64,
+    pub r8: u64,
+    pub rax: u64,
+    pub rcx: u64,
+    pub rdx: u64,
+    pub rsi: u64,
+    pub rdi: u64,
+    pub orig_rax: u64,
+    pub rip: u64,
+    pub cs: u64,
+    pub eflags: u64,
+    pub rsp: u64,
+    pub ss: u64,
+    pub fs_base: u64,
+    pub gs_base: u64,
+    pub ds: u64,
+    pub es: u64,
+    pub fs: u64,
+    pub gs: u64,
+}
+
+/// Process information from a core file (`struct elf_prpsinfo`)
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct PrPsInfo {
+    /// Numeric process state, and its letter as in `ps` (e.g. `R`)
+    pub state: u8,
+    pub sname: char,
+    pub zombie: bool,
+    pub nice: i8,
+    /// Kernel process flags (`PF_*`)
+    pub flag: u64,
+    pub uid: u32,
+    pub gid: u32,
+    pub pid: u32,
+    pub ppid: u32,
+    pub pgrp: u32,
+    pub sid: u32,
+    /// Executable name, truncated to 15 bytes
+    pub fname: String,
+    /// Start of the command line, arguments separated by spaces
+    pub psargs: String,
+}
+
+/// Pointer encodings used by `.eh_frame` and `.eh_frame_hdr` (low nibble:
+/// value format, high nibble: what it is relative to)
+pub const DW_EH_PE_ABSPTR: u8 = 0x00;
+pub const DW_EH_PE_ULEB128: u8 = 0x01;
+pub const DW_EH_PE_UDATA2: u8 = 0x02;
+pub const DW_EH_PE_UDATA4: u8 = 0x03;
+pub const DW_EH_PE_UDATA8: u8 = 0x04;
+pub const DW_EH_PE_SLEB128: u8 = 0x09;
+pub const DW_EH_PE_SDATA2: u8 = 0x0a;
+pub const DW_EH_PE_SDATA4: u8 = 0x0b;
+pub const DW_EH_PE_SDATA8: u8 = 0x0c;
+pub const DW_EH_PE_PCREL: u8 = 0x10;
+pub const DW_EH_PE_TEXTREL: u8 = 0x20;
+pub const DW_EH_PE_DATAREL: u8 = 0x30;
+pub const DW_EH_PE_FUNCREL: u8 = 0x40;
+pub const DW_EH_PE_ALIGNED: u8 = 0x50;
+pub const DW_EH_PE_INDIRECT: u8 = 0x80;
+pub const DW_EH_PE_OMIT: u8 = 0xff;
+
+/// Contents of `.eh_frame_hdr`, the lookup table for unwind information
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct EhFrameHdr {
+    pub version: u8,
+    pub eh_frame_ptr_enc: u8,
+    pub fde_count_enc: u8,
+    pub table_enc: u8,
+    /// Address of `.eh_frame`
+    pub eh_frame_ptr: u64,
+    /// `(initial location, FDE address)` pairs, sorted by initial location
+    pub table: Vec<(u64, u64)>,
+}
@@ -1,103 +1,450 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+#[cfg(feature = "std")]
 extern crate byteorder;
+#[cfg(feature = "std")]
 extern crate libc;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "demangle")]
+extern crate rustc_demangle;
+#[cfg(feature = "demangle")]
+extern crate cpp_demangle;
+#[cfg(feature = "compression")]
+extern crate flate2;
+#[cfg(feature = "xz")]
+extern crate lzma_rs;
+#[cfg(feature = "hashing")]
+extern crate sha2;
+#[cfg(feature = "dwarf")]
+extern crate gimli;
+#[cfg(feature = "disasm")]
+extern crate capstone;
+#[cfg(feature = "async")]
+extern crate tokio;
+#[cfg(feature = "rayon")]
+extern crate rayon;
 
+#[cfg(feature = "std")]
 pub mod pe;
 pub mod elf;
+#[cfg(feature = "std")]
 pub mod mach;
+#[cfg(feature = "std")]
+pub mod archive;
+#[cfg(feature = "std")]
+pub mod wasm;
+pub mod source;
+#[cfg(feature = "disasm")]
+pub mod disasm;
+#[cfg(feature = "serde")]
+mod ser;
+
+/// Without the `std` feature, lets `std::` paths resolve to `core` and `alloc`
+#[cfg(not(feature = "std"))]
+mod std {
+    pub use core::*;
+    pub use alloc::{borrow, string, vec};
+}
 
+#[cfg(feature = "std")]
+use std::io::prelude::*;
+#[cfg(feature = "std")]
 use std::io;
+#[cfg(feature = "std")]
 use std::error;
+use std::borrow::Cow;
+use std::string::String;
+use std::vec::Vec;
 use std::fmt;
+use std::str;
+use std::cmp;
 use std::default;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The file does not start with the expected magic number/signature
+    BadMagic,
+    /// The file class (32/64-bit) is not recognised
+    InvalidClass,
+    /// The data encoding (endianness) is not recognised
+    InvalidEndianness,
+    /// Data claimed by a header extends past the end of the input
+    Truncated { offset: u64 },
+    /// A table's entry size is zero or does not divide its total size
+    InvalidEntsize,
+    /// An underlying I/O operation failed
+    #[cfg(feature = "std")]
+    Io(io::Error),
+    /// The input does not match any supported format
+    UnknownFormat,
+    /// Any other failure, described by a message
+    Other(String),
+}
 
-pub struct Error {
-    inner: Option<Box<error::Error>>,
-    desc: String,
+impl Error {
+    fn description_str(&self) -> &str {
+        match *self {
+            Error::BadMagic => "invalid magic number",
+            Error::InvalidClass => "invalid class",
+            Error::InvalidEndianness => "invalid endianness",
+            Error::Truncated { .. } => "truncated input",
+            Error::InvalidEntsize => "invalid table entsize",
+            #[cfg(feature = "std")]
+            Error::Io(_) => "I/O error",
+            Error::UnknownFormat => "unknown file format",
+            Error::Other(ref desc) => desc,
+        }
+    }
 }
 
+#[cfg(feature = "std")]
 impl error::Error for Error {
     fn description(&self) -> &str {
-        &self.desc
+        self.description_str()
     }
     fn cause(&self) -> Option<&error::Error> {
-        self.inner.as_ref().map(|x| &**x)
+        match *self {
+            Error::Io(ref x) => Some(x),
+            _ => None,
+        }
     }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
-        write!(w, "Error: {}{}", self.desc, match self.inner {
-            Some(ref x) => {
-                format!(" ({})", x.description())
-            }
-            None => format!(""),
-        })
+        match *self {
+            Error::Truncated { offset } => write!(w, "Error: truncated input (at offset {:#x})", offset),
+            #[cfg(feature = "std")]
+            Error::Io(ref x) => write!(w, "Error: I/O error ({})", x),
+            _ => write!(w, "Error: {}", self.description_str()),
+        }
     }
 }
 
-impl fmt::Debug for Error {
-    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
-        write!(w, "execfmt error: desc: {} inner: {}", self.desc, match self.inner {
-            Some(ref x) => x.description(),
-            None => "None",
-        })
+impl<'a> From<&'a str> for Error {
+    fn from(s: &'a str) -> Error {
+        Error::Other(String::from(s))
     }
 }
 
-impl<'a> From<&'a str> for Error {
-    fn from(s: &'a str) -> Error {
-        Error {
-            inner: None,
-            desc: String::from(s),
-        }
+#[cfg(feature = "std")]
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::Io(e)
     }
 }
 
-#[derive(Clone, Debug, Copy)]
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
 pub enum Arch {
     X86(Width),
     ARM(Width, Endianness, ARMMode, ARMType),
     PPC(Width, Endianness),
+    MIPS(Width, Endianness),
+    SPARC(Width, Endianness),
+    RISCV(Width, Endianness),
+    S390(Width, Endianness),
+    /// WebAssembly bytecode
+    Wasm,
     Unknown,
 }
 
-#[derive(Clone, Debug, Copy)]
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
 pub enum Endianness {
     Little,
     Big,
 }
 
-#[derive(Clone, Debug, Copy)]
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
 pub enum Width {
     W16,
     W32,
     W64,
 }
 
-#[derive(Clone, Debug, Copy)]
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
 pub enum ARMMode {
     ARM,
     Thumb,
 }
 
-#[derive(Clone, Debug, Copy)]
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
 pub enum ARMType {
     ARM,
     MClass,
     V8,
 }
 
+/// Names of the architectures spelled after one pattern: the 32- and
+/// 64-bit names, the usual byte order, and the suffix marking the other one
+const ARCH_FAMILIES: [(&str, &str, Endianness, &str); 5] = [
+    ("ppc", "ppc64", Endianness::Big, "le"),
+    ("mips", "mips64", Endianness::Big, "el"),
+    ("sparc", "sparc64", Endianness::Big, "el"),
+    ("riscv32", "riscv64", Endianness::Little, "be"),
+    ("s390", "s390x", Endianness::Big, "le"),
+];
+
+impl Arch {
+    /// Index into `ARCH_FAMILIES`, with the width and byte order
+    fn family(&self) -> Option<(usize, Width, Endianness)> {
+        match *self {
+            Arch::PPC(w, e) => Some((0, w, e)),
+            Arch::MIPS(w, e) => Some((1, w, e)),
+            Arch::SPARC(w, e) => Some((2, w, e)),
+            Arch::RISCV(w, e) => Some((3, w, e)),
+            Arch::S390(w, e) => Some((4, w, e)),
+            _ => None,
+        }
+    }
+    fn from_family(index: usize, width: Width, endian: Endianness) -> Arch {
+        match index {
+            0 => Arch::PPC(width, endian),
+            1 => Arch::MIPS(width, endian),
+            2 => Arch::SPARC(width, endian),
+            3 => Arch::RISCV(width, endian),
+            _ => Arch::S390(width, endian),
+        }
+    }
+    fn parse_family(s: &str) -> Option<Arch> {
+        for (i, &(name32, name64, endian, suffix)) in ARCH_FAMILIES.iter().enumerate() {
+            for &(name, width) in [(name32, Width::W32), (name64, Width::W64)].iter() {
+                if s == name {
+                    return Some(Arch::from_family(i, width, endian));
+                }
+                if s.len() == name.len() + suffix.len() && s.starts_with(name) && s.ends_with(suffix) {
+                    return Some(Arch::from_family(i, width, endian.other()));
+                }
+            }
+        }
+        None
+    }
+    /// `arm`/`thumb`, then `v7m` (M-profile) or `v8`, then `eb` if big-endian
+    fn parse_arm32(s: &str) -> Option<Arch> {
+        let (mode, rest) = match (s.strip_prefix("thumb"), s.strip_prefix("arm")) {
+            (Some(rest), _) => (ARMMode::Thumb, rest),
+            (_, Some(rest)) => (ARMMode::ARM, rest),
+            _ => return None,
+        };
+        let (endian, rest) = match rest.strip_suffix("eb") {
+            Some(rest) => (Endianness::Big, rest),
+            None => (Endianness::Little, rest),
+        };
+        let arm_type = match rest {
+            "" => ARMType::ARM,
+            "v7m" => ARMType::MClass,
+            "v8" => ARMType::V8,
+            _ => return None,
+        };
+        Some(Arch::ARM(Width::W32, endian, mode, arm_type))
+    }
+}
+
+/// Short lowercase name in the style of target triples (`x86_64`,
+/// `armeb`, `thumbv7m`, `aarch64`, `mips64el`, `riscv64`, ...), which
+/// `str::parse` reads back
+///
+/// Widths a family has no name for (16-bit anything other than x86) are
+/// shown as 32-bit.
+impl fmt::Display for Arch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some((i, width, endian)) = self.family() {
+            let (name32, name64, usual, suffix) = ARCH_FAMILIES[i];
+            let name = if width == Width::W64 { name64 } else { name32 };
+            let suffix = if endian == usual { "" } else { suffix };
+            return write!(f, "{}{}", name, suffix);
+        }
+        match *self {
+            Arch::X86(Width::W16) => f.write_str("i8086"),
+            Arch::X86(Width::W32) => f.write_str("x86"),
+            Arch::X86(Width::W64) => f.write_str("x86_64"),
+            Arch::ARM(Width::W64, endian, _, _) => {
+                write!(f, "aarch64{}", if endian == Endianness::Big { "_be" } else { "" })
+            },
+            Arch::ARM(_, endian, mode, arm_type) => {
+                let mode = match mode {
+                    ARMMode::ARM => "arm",
+                    ARMMode::Thumb => "thumb",
+                };
+                let arm_type = match arm_type {
+                    ARMType::ARM => "",
+                    ARMType::MClass => "v7m",
+                    ARMType::V8 => "v8",
+                };
+                write!(f, "{}{}{}", mode, arm_type, if endian == Endianness::Big { "eb" } else { "" })
+            },
+            Arch::Wasm => f.write_str("wasm"),
+            _ => f.write_str("unknown"),
+        }
+    }
+}
+
+/// Reads the names written by `Display`, plus the common aliases `i386`,
+/// `i686`, `amd64`, `arm64` and `wasm32`
+impl str::FromStr for Arch {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Arch, Error> {
+        let arch = match s {
+            "i8086" => Arch::X86(Width::W16),
+            "x86" | "i386" | "i686" => Arch::X86(Width::W32),
+            "x86_64" | "amd64" => Arch::X86(Width::W64),
+            "aarch64" | "arm64" => Arch::ARM(Width::W64, Endianness::Little, ARMMode::ARM, ARMType::V8),
+            "aarch64_be" => Arch::ARM(Width::W64, Endianness::Big, ARMMode::ARM, ARMType::V8),
+            "wasm" | "wasm32" => Arch::Wasm,
+            "unknown" => Arch::Unknown,
+            _ => match Arch::parse_family(s).or_else(|| Arch::parse_arm32(s)) {
+                Some(arch) => arch,
+                None => return Err(Error::from("unknown architecture name")),
+            },
+        };
+        Ok(arch)
+    }
+}
+
+impl Endianness {
+    fn other(self) -> Endianness {
+        match self {
+            Endianness::Little => Endianness::Big,
+            Endianness::Big => Endianness::Little,
+        }
+    }
+}
+
+/// `little` or `big`
+impl fmt::Display for Endianness {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Endianness::Little => "little",
+            Endianness::Big => "big",
+        })
+    }
+}
+
+/// Reads `little`/`big`, or the short forms `le`/`be`
+impl str::FromStr for Endianness {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Endianness, Error> {
+        match s {
+            "little" | "le" => Ok(Endianness::Little),
+            "big" | "be" => Ok(Endianness::Big),
+            _ => Err(Error::from("unknown endianness name")),
+        }
+    }
+}
+
+/// `w16`, `w32` or `w64`
+impl fmt::Display for Width {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Width::W16 => "w16",
+            Width::W32 => "w32",
+            Width::W64 => "w64",
+        })
+    }
+}
+
+/// Reads the names written by `Display`, or just the number of bits
+impl str::FromStr for Width {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Width, Error> {
+        match s {
+            "w16" | "16" => Ok(Width::W16),
+            "w32" | "32" => Ok(Width::W32),
+            "w64" | "64" => Ok(Width::W64),
+            _ => Err(Error::from("unknown width name")),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+pub enum ObjectType {
+    Relocatable,
+    Executable,
+    SharedObject,
+    Core,
+    Unknown,
+}
+
+/// The container format a parser understands
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+pub enum Format {
+    Elf,
+    Pe,
+    MachO,
+    Wasm,
+    /// `ar` static library, as read by `archive::Archive`
+    Archive,
+}
+
+/// What a `Symbol` names
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum SymbolKind {
+    Function,
+    Data,
+    Section,
+    /// Source file name
+    File,
+    Unknown,
+}
+
+/// A symbol from any format's symbol table, as returned by `Object::symbols`
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Symbol {
+    pub name: String,
+    /// Virtual address, or offset within its section in relocatable files
+    pub address: u64,
+    /// Size in bytes, or 0 where the format doesn't record one
+    pub size: u64,
+    pub kind: SymbolKind,
+    /// Visible to other files (global or weak binding)
+    pub global: bool,
+    /// Defined in this file rather than imported from elsewhere
+    pub defined: bool,
+}
+
 pub trait Object {
+    /// Which parser produced this object
+    fn format(&self) -> Format;
+    /// The file's symbols in the common form, in symbol table order
+    fn symbols(&self) -> Vec<Symbol>;
     fn arch(&self) -> Arch;
     fn get_section(&self, name: &str) -> Option<&Section>;
+    fn entry(&self) -> u64;
+    fn obj_type(&self) -> ObjectType;
+    /// Byte order of the file's headers and data
+    fn endianness(&self) -> Endianness;
+    /// Size of an address in the file
+    fn width(&self) -> Width;
 }
 
-#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Section {
     name: String,
+    index: usize,
     addr: u64,
     offset: u64,
     size: u64,
+    flags: elf::types::SectionFlag,
+    shtype: elf::types::SectionType,
+    link: u32,
+    info: u32,
+    entsize: u64,
+    addralign: u64,
+    nobits: bool,
+    compressed_size: Option<u64>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    loaded: bool,
+    #[cfg_attr(all(feature = "serde", not(feature = "serde-data")), serde(skip))]
+    #[cfg_attr(feature = "serde-data", serde(serialize_with = "::ser::base64"))]
     data: Vec<u8>,
 }
 
@@ -105,6 +452,11 @@ impl Section {
     pub fn name(&self) -> &str {
         &self.name
     }
+    /// Position in the file's section table, as referred to by `link()`
+    /// and `info()`
+    pub fn index(&self) -> usize {
+        self.index
+    }
     pub fn addr(&self) -> u64 {
         self.addr
     }
@@ -117,28 +469,334 @@ impl Section {
     pub fn data(&self) -> &[u8] {
         &self.data
     }
+    /// Section flags, using the ELF `SHF_*` bits (other formats are mapped
+    /// onto the closest equivalents)
+    pub fn flags(&self) -> elf::types::SectionFlag {
+        self.flags
+    }
+    /// Section type, using the ELF `SHT_*` values (other formats report
+    /// `SHT_PROGBITS`, or `SHT_NOBITS` for zero-fill sections)
+    pub fn shtype(&self) -> elf::types::SectionType {
+        self.shtype
+    }
+    /// Index of an associated section (`sh_link`), whose meaning depends on `shtype()`
+    pub fn link(&self) -> u32 {
+        self.link
+    }
+    /// Extra type-dependent information (`sh_info`)
+    pub fn info(&self) -> u32 {
+        self.info
+    }
+    /// Size of each entry for sections holding a table, otherwise 0
+    pub fn entsize(&self) -> u64 {
+        self.entsize
+    }
+    /// Required alignment of `addr()` (`sh_addralign`), with 0 or 1 meaning
+    /// none; 0 where the format doesn't record it (PE images, WebAssembly)
+    pub fn addralign(&self) -> u64 {
+        self.addralign
+    }
+    pub fn is_writable(&self) -> bool {
+        self.flags.contains(elf::types::SHF_WRITE)
+    }
+    pub fn is_executable(&self) -> bool {
+        self.flags.contains(elf::types::SHF_EXECINSTR)
+    }
+    pub fn is_alloc(&self) -> bool {
+        self.flags.contains(elf::types::SHF_ALLOC)
+    }
+    /// Section occupies no space in the file (e.g. `.bss`), so `data()` is
+    /// empty even though `size()` reports its in-memory size
+    pub fn is_nobits(&self) -> bool {
+        self.nobits
+    }
+    /// Size of the section in the file if `data()` was decompressed from it
+    pub fn compressed_size(&self) -> Option<u64> {
+        self.compressed_size
+    }
+    /// Replace the contents of the section with data of the same size (so
+    /// the file can be written back with `elf::File::write`)
+    pub fn set_data(&mut self, data: Vec<u8>) -> Result<(), Error> {
+        if self.nobits {
+            return Err(Error::from("section occupies no space in the file"));
+        }
+        if data.len() as u64 != self.size {
+            return Err(Error::from("replacement data must be the same size as the section"));
+        }
+        self.data = data;
+        self.loaded = true;
+        Ok(())
+    }
+    /// Whether `data()` holds the section contents (see `elf::File::parse_lazy`)
+    pub fn is_loaded(&self) -> bool {
+        self.loaded
+    }
+    /// Section contents without copying: `data()` if the section has been
+    /// read, otherwise its extent within `input`, the buffer the file was
+    /// parsed from. `None` if that extent lies outside `input`.
+    ///
+    /// A section doesn't know which buffer it came from, so passing any
+    /// other buffer silently gives whatever bytes sit at the section's
+    /// offset in it; for ELF files `elf::File::section_data_in` also checks
+    /// that `input` has the parsed file's length.
+    pub fn data_in<'a>(&'a self, input: &'a [u8]) -> Option<&'a [u8]> {
+        if self.loaded {
+            return Some(&self.data);
+        }
+        let end = match self.offset.checked_add(self.size) {
+            Some(end) if end <= input.len() as u64 => end as usize,
+            _ => return None,
+        };
+        Some(&input[self.offset as usize..end])
+    }
+    /// Each NUL-terminated string in `data()`, for string table sections
+    /// such as `.strtab`, `.dynstr` and `.comment`
+    ///
+    /// Empty strings (such as the one at offset 0) are skipped, and a final
+    /// string with no terminator is still yielded. Invalid UTF-8 is
+    /// replaced with U+FFFD.
+    pub fn strings(&self) -> impl Iterator<Item = Cow<'_, str>> {
+        self.data.split(|b| *b == 0)
+            .filter(|s| !s.is_empty())
+            .map(String::from_utf8_lossy)
+    }
+    /// CRC-32 (IEEE 802.3, as used by zlib) of `data()`
+    pub fn crc32(&self) -> u32 {
+        let mut crc = !0u32;
+        for &b in self.data.iter() {
+            crc ^= b as u32;
+            for _ in 0..8 {
+                crc = if crc & 1 != 0 { (crc >> 1) ^ 0xedb88320 } else { crc >> 1 };
+            }
+        }
+        !crc
+    }
+    /// Shannon entropy of `data()` in bits per byte, from 0 (empty or a
+    /// single repeated byte) to 8 (uniformly random)
+    #[cfg(feature = "std")]
+    pub fn entropy(&self) -> f64 {
+        let mut counts = [0u64; 256];
+        for &b in self.data.iter() {
+            counts[b as usize] += 1;
+        }
+        let len = self.data.len() as f64;
+        counts.iter().filter(|&&n| n != 0).fold(0.0, |h, &n| {
+            let p = n as f64 / len;
+            h - p * p.log2()
+        })
+    }
+    /// SHA-256 digest of `data()`
+    #[cfg(feature = "hashing")]
+    pub fn sha256(&self) -> [u8; 32] {
+        use sha2::{Digest, Sha256};
+
+        let mut out = [0u8; 32];
+        out.copy_from_slice(&Sha256::digest(&self.data));
+        out
+    }
+    /// `data()` in the layout of `xxd`, with the offset column counting from `addr()`
+    pub fn hexdump(&self) -> String {
+        self.hexdump_range(0, self.data.len())
+    }
+    /// Like `hexdump`, but only the `len` bytes starting `start` bytes into
+    /// the section (clamped to the end of `data()`)
+    pub fn hexdump_range(&self, start: usize, len: usize) -> String {
+        use std::fmt::Write;
+
+        let end = cmp::min(start.saturating_add(len), self.data.len());
+        let start = cmp::min(start, end);
+        let mut out = String::new();
+        for (i, line) in self.data[start..end].chunks(16).enumerate() {
+            let _ = write!(out, "{:08x}:", self.addr.wrapping_add((start + i * 16) as u64));
+            for j in 0..16 {
+                if j % 2 == 0 {
+                    out.push(' ');
+                }
+                match line.get(j) {
+                    Some(b) => { let _ = write!(out, "{:02x}", b); },
+                    None => out.push_str("  "),
+                }
+            }
+            out.push_str("  ");
+            for &b in line {
+                out.push(if (0x20..0x7f).contains(&b) { b as char } else { '.' });
+            }
+            out.push('\n');
+        }
+        out
+    }
+    /// Read the contents of a deferred section from `r`, which must be the
+    /// input the file was parsed from
+    #[cfg(feature = "std")]
+    pub fn load<R: io::Read + io::Seek>(&mut self, r: &mut R) -> Result<(), Error> {
+        if self.loaded {
+            return Ok(());
+        }
+        let file_len = try!(r.seek(io::SeekFrom::End(0)));
+        if self.offset.checked_add(self.size).map_or(true, |end| end > file_len) {
+            return Err(Error::Truncated { offset: self.offset });
+        }
+        try!(r.seek(io::SeekFrom::Start(self.offset)));
+        let mut data = vec![0; self.size as usize];
+        try!(r.read_exact(&mut data));
+        self.data = data;
+        self.loaded = true;
+        Ok(())
+    }
+}
+
+impl Section {
+    /// Column headings matching the lines written by `Display`
+    fn fmt_table_header(f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "  [Nr] Name                 Type               Address          Off      Size     ES   Flg  Lk Inf Al")
+    }
+}
+
+/// A row in the style of `readelf -S`, led by the section's index, with
+/// fields aligned into columns, so a `{:#?}` dump of a file's sections
+/// reads like the section table; a section without a name (such as the
+/// `SHT_NULL` entry at index 0) is shown as `(null)`
+impl fmt::Debug for Section {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = if self.name.is_empty() { "(null)" } else { &self.name[..] };
+        write!(f, "  [{:>2}] {:<20} {:<18} {:016x} {:08x} {:08x} {:04x} {:<3} {:>3} {:>3} {}",
+               self.index, name, self.shtype, self.addr, self.offset, self.size, self.entsize,
+               self.flags, self.link, self.info, self.addralign)
+    }
+}
+
+/// The `Debug` row on a line of its own, as listed under
+/// `fmt_table_header` by `Display for elf::File`
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:?}", self)
+    }
 }
 
 impl default::Default for Section {
     fn default() -> Section {
         Section {
             name: String::from(""),
+            index: 0,
             addr: 0,
             offset: 0,
             size: u64::max_value(),
+            flags: elf::types::SectionFlag(0),
+            shtype: elf::types::SHT_NULL,
+            link: 0,
+            info: 0,
+            entsize: 0,
+            addralign: 0,
+            nobits: false,
+            compressed_size: None,
+            loaded: true,
             data: Vec::new(),
         }
     }
 }
 
-pub fn parse<R: io::Read + io::Seek>(r: &mut R) -> Result<Box<Object>, Box<error::Error>> {
-    if let Ok(x) = elf::File::parse(r) {
-        Ok(Box::new(x))
-    } else if let Ok(x) = pe::File::parse(r) {
-        Ok(Box::new(x))
-    } else if let Ok(x) = mach::File::parse(r) {
-        Ok(Box::new(x))
-    } else {
-        Err(Box::new(Error::from("Invalid format")))
+/// Demangle a Rust or C++ (Itanium) symbol name, returning it unchanged if it is neither
+#[cfg(feature = "demangle")]
+pub fn demangle(name: &str) -> String {
+    // Legacy Rust names are also valid Itanium names, so try Rust first
+    if let Ok(sym) = rustc_demangle::try_demangle(name) {
+        return format!("{:#}", sym);
+    }
+    match cpp_demangle::Symbol::new(name) {
+        Ok(sym) => sym.to_string(),
+        Err(_) => String::from(name),
+    }
+}
+
+/// Parse any supported format, chosen by the magic number at the start of `r`
+#[cfg(feature = "std")]
+pub fn parse<R: io::Read + io::Seek>(r: &mut R) -> Result<Box<Object>, Error> {
+    let mut magic = [0u8; 4];
+    try!(r.seek(io::SeekFrom::Start(0)));
+    if r.read_exact(&mut magic).is_err() {
+        return Err(Error::UnknownFormat);
+    }
+
+    if magic == elf::types::ELFMAG {
+        return Ok(Box::new(try!(elf::File::parse(r))));
+    }
+    if &magic[0..2] == b"MZ" {
+        return Ok(Box::new(try!(pe::File::parse(r))));
+    }
+    if magic == wasm::types::WASM_MAGIC {
+        return Ok(Box::new(try!(wasm::File::parse(r))));
+    }
+
+    let word = (magic[0] as u32) << 24 | (magic[1] as u32) << 16 | (magic[2] as u32) << 8 | magic[3] as u32;
+    match word {
+        mach::types::MACH_HDR_MAG | mach::types::MACH_HDR_CIG |
+        mach::types::MACH64_HDR_MAG | mach::types::MACH64_HDR_CIG => Ok(Box::new(try!(mach::File::parse(r)))),
+        // Use mach::FatFile directly to get at the other slices
+        mach::types::FAT_MAGIC | mach::types::FAT_MAGIC_64 => match try!(mach::FatFile::parse(r)).into_slices().into_iter().next() {
+            Some((_, x)) => Ok(Box::new(x)),
+            None => Err(Error::from("fat binary has no slices")),
+        },
+        _ => Err(Error::UnknownFormat),
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arch_names_round_trip() {
+        let mut all = vec![Arch::X86(Width::W16), Arch::X86(Width::W32), Arch::X86(Width::W64), Arch::Wasm, Arch::Unknown];
+        for &width in [Width::W32, Width::W64].iter() {
+            for &endian in [Endianness::Little, Endianness::Big].iter() {
+                all.extend([Arch::PPC(width, endian), Arch::MIPS(width, endian), Arch::SPARC(width, endian),
+                            Arch::RISCV(width, endian), Arch::S390(width, endian)].iter().cloned());
+            }
+        }
+        for &endian in [Endianness::Little, Endianness::Big].iter() {
+            all.push(Arch::ARM(Width::W64, endian, ARMMode::ARM, ARMType::V8));
+            for &mode in [ARMMode::ARM, ARMMode::Thumb].iter() {
+                for &arm_type in [ARMType::ARM, ARMType::MClass, ARMType::V8].iter() {
+                    all.push(Arch::ARM(Width::W32, endian, mode, arm_type));
+                }
+            }
+        }
+        for arch in all {
+            let name = arch.to_string();
+            assert_eq!(name.parse::<Arch>().unwrap(), arch, "{}", name);
+        }
+    }
+
+    #[test]
+    fn arch_names() {
+        assert_eq!(Arch::X86(Width::W64).to_string(), "x86_64");
+        assert_eq!(Arch::MIPS(Width::W64, Endianness::Little).to_string(), "mips64el");
+        assert_eq!(Arch::ARM(Width::W32, Endianness::Little, ARMMode::Thumb, ARMType::MClass).to_string(), "thumbv7m");
+        assert_eq!("amd64".parse::<Arch>().unwrap(), Arch::X86(Width::W64));
+        assert_eq!("arm64".parse::<Arch>().unwrap(), Arch::ARM(Width::W64, Endianness::Little, ARMMode::ARM, ARMType::V8));
+        assert!("z80".parse::<Arch>().is_err());
+    }
+
+    #[test]
+    fn width_and_endianness_names_round_trip() {
+        for &width in [Width::W16, Width::W32, Width::W64].iter() {
+            assert_eq!(width.to_string().parse::<Width>().unwrap(), width);
+        }
+        assert_eq!(Width::W64.to_string(), "w64");
+        assert_eq!("32".parse::<Width>().unwrap(), Width::W32);
+        for &endian in [Endianness::Little, Endianness::Big].iter() {
+            assert_eq!(endian.to_string().parse::<Endianness>().unwrap(), endian);
+        }
+        assert_eq!(Endianness::Little.to_string(), "little");
+        assert_eq!("be".parse::<Endianness>().unwrap(), Endianness::Big);
+        assert!("middle".parse::<Endianness>().is_err());
+    }
+
+    #[test]
+    fn section_debug_is_an_indexed_table_row() {
+        let section = Section { name: String::from(".text"), index: 3, size: 0x10, ..Section::default() };
+        let row = format!("{:?}", section);
+        assert!(row.starts_with("  [ 3] .text                SHT_NULL"), "{}", row);
+        assert_eq!(format!("{}", section), row + "\n");
     }
 }
@@ -0,0 +1,92 @@
+use std::io::prelude::*;
+use std::io;
+use byteorder;
+use byteorder::ReadBytesExt;
+use mach::types;
+use mach::File;
+use {Error, Object};
+
+macro_rules! read_u32 {
+    ($io:ident) => (
+        $io.read_u32::<byteorder::BigEndian>()
+    );
+}
+
+macro_rules! read_u64 {
+    ($io:ident) => (
+        $io.read_u64::<byteorder::BigEndian>()
+    );
+}
+
+/// A universal (fat) binary holding one Mach-O file per architecture
+pub struct FatFile {
+    arches: Vec<types::FatArch>,
+    slices: Vec<(::Arch, File)>,
+}
+
+impl FatFile {
+    pub fn parse<R: io::Read + io::Seek>(r: &mut R) -> Result<FatFile, Error> {
+        let file_len = try!(r.seek(io::SeekFrom::End(0)));
+        try!(r.seek(io::SeekFrom::Start(0)));
+
+        let magic = try!(read_u32!(r));
+        if magic != types::FAT_MAGIC && magic != types::FAT_MAGIC_64 {
+            try!(Err(Error::BadMagic));
+        }
+        let nfat_arch = try!(read_u32!(r));
+        // Java class files share FAT_MAGIC; their major version (>= 45) lands here
+        if nfat_arch >= types::FAT_MAX_ARCH {
+            try!(Err(Error::BadMagic));
+        }
+
+        let mut arches = Vec::new();
+        for _ in 0..nfat_arch {
+            let cpu_type = types::Machine(try!(read_u32!(r)) as i32);
+            let cpu_subtype = try!(read_u32!(r)) & !types::CPU_SUBTYPE_MASK;
+            let (offset, size) = if magic == types::FAT_MAGIC_64 {
+                (try!(read_u64!(r)), try!(read_u64!(r)))
+            } else {
+                (try!(read_u32!(r)) as u64, try!(read_u32!(r)) as u64)
+            };
+            let align = try!(read_u32!(r));
+            if magic == types::FAT_MAGIC_64 {
+                // reserved
+                try!(read_u32!(r));
+            }
+            if offset.checked_add(size).map_or(true, |end| end > file_len) {
+                return Err(Error::Truncated { offset: offset });
+            }
+            arches.push(types::FatArch {
+                cpu_type: cpu_type,
+                cpu_subtype: cpu_subtype,
+                offset: offset,
+                size: size,
+                align: align,
+            });
+        }
+
+        let mut slices = Vec::new();
+        for arch in &arches {
+            try!(r.seek(io::SeekFrom::Start(arch.offset)));
+            let buf: Vec<u8> = try!(io::Read::by_ref(r).bytes().take(arch.size as usize).collect());
+            let file = try!(File::parse(&mut io::Cursor::new(buf)));
+            slices.push((file.arch(), file));
+        }
+
+        Ok(FatFile {
+            arches: arches,
+            slices: slices,
+        })
+    }
+    /// The fat_arch table, in file order
+    pub fn arches(&self) -> &[types::FatArch] {
+        &self.arches
+    }
+    /// Each architecture's Mach-O file, in fat_arch table order
+    pub fn slices(&self) -> &[(::Arch, File)] {
+        &self.slices
+    }
+    pub fn into_slices(self) -> Vec<(::Arch, File)> {
+        self.slices
+    }
+}
@@ -1,8 +1,8 @@
 use std::io::prelude::*;
 use std::io;
-use std::error;
 use std::collections::HashMap;
 use mach::types;
+use elf;
 use byteorder;
 use byteorder::ReadBytesExt;
 use {Error, Section, Object};
@@ -18,7 +18,7 @@ macro_rules! read_u16 {
         match $data {
             types::MACH_DATA_2LSB => { $io.read_u16::<byteorder::LittleEndian>() },
             types::MACH_DATA_2MSB => { $io.read_u16::<byteorder::BigEndian>()},
-            _ => { try!(Err(Error::from("invalid endianness"))) },
+            _ => { try!(Err(Error::InvalidEndianness)) },
         }
     );
 }
@@ -27,7 +27,7 @@ macro_rules! read_u32 {
         match $data {
             types::MACH_DATA_2LSB => { $io.read_u32::<byteorder::LittleEndian>() },
             types::MACH_DATA_2MSB => { $io.read_u32::<byteorder::BigEndian>()},
-            _ => { try!(Err(Error::from("invalid endianness"))) },
+            _ => { try!(Err(Error::InvalidEndianness)) },
         }
     );
 }
@@ -36,7 +36,7 @@ macro_rules! read_i32 {
         match $data {
             types::MACH_DATA_2LSB => { $io.read_i32::<byteorder::LittleEndian>() },
             types::MACH_DATA_2MSB => { $io.read_i32::<byteorder::BigEndian>()},
-            _ => { try!(Err(Error::from("invalid endianness"))) },
+            _ => { try!(Err(Error::InvalidEndianness)) },
         }
     );
 }
@@ -45,7 +45,7 @@ macro_rules! read_i64 {
         match $data {
             types::MACH_DATA_2LSB => { $io.read_i64::<byteorder::LittleEndian>() },
             types::MACH_DATA_2MSB => { $io.read_i64::<byteorder::BigEndian>()},
-            _ => { try!(Err(Error::from("invalid endianness"))) },
+            _ => { try!(Err(Error::InvalidEndianness)) },
         }
     );
 }
@@ -54,19 +54,101 @@ macro_rules! read_u64 {
         match $data {
             types::MACH_DATA_2LSB => { $io.read_u64::<byteorder::LittleEndian>() },
             types::MACH_DATA_2MSB => { $io.read_u64::<byteorder::BigEndian>()},
-            _ => { try!(Err(Error::from("invalid endianness"))) },
+            _ => { try!(Err(Error::InvalidEndianness)) },
         }
     );
 }
 
+macro_rules! read_addr {
+    ($class:ident, $data:ident, $io:ident) => (
+        if $class == types::MACH_CLASS_64 {
+            try!(read_u64!($data, $io))
+        } else {
+            try!(read_u32!($data, $io)) as u64
+        }
+    );
+}
+
+fn read_name<R: io::Read>(r: &mut R) -> Result<String, Error> {
+    let mut name_buf = [0u8; 16];
+    try!(r.read_exact(&mut name_buf));
+    let len = name_buf.iter().position(|x| *x == 0).unwrap_or(name_buf.len());
+    Ok(String::from_utf8_lossy(&name_buf[..len]).into_owned())
+}
+
+/// Program counter from an LC_UNIXTHREAD thread state, for the flavors we know
+fn thread_pc<R: io::Read>(data: types::Data, cpu_type: types::Machine, r: &mut R) -> Result<Option<u64>, Error> {
+    let flavor = try!(read_u32!(data, r));
+    let _count = try!(read_u32!(data, r));
+    // Index of the program counter within the register array
+    let (wide, pc_index) = match (cpu_type, flavor) {
+        (types::PM_X86_64, types::X86_THREAD_STATE64) => (true, 16),
+        (types::PM_I386, types::X86_THREAD_STATE32) => (false, 10),
+        (types::PM_ARM64, types::ARM_THREAD_STATE64) => (true, 32),
+        (types::PM_ARM, types::ARM_THREAD_STATE) => (false, 15),
+        _ => return Ok(None),
+    };
+    let mut pc = 0;
+    for _ in 0..(pc_index + 1) {
+        pc = if wide { try!(read_u64!(data, r)) } else { try!(read_u32!(data, r)) as u64 };
+    }
+    Ok(Some(pc))
+}
+
+/// Read the `nlist` entries and their names described by an `LC_SYMTAB`
+/// (`symoff`, `nsyms`, `stroff`, `strsize`)
+fn read_symbols<R: io::Read + io::Seek>(r: &mut R, class: types::Class, data: types::Data, file_len: u64, symtab: (u64, u64, u64, u64)) -> Result<Vec<types::Nlist>, Error> {
+    let (symoff, nsyms, stroff, strsize) = symtab;
+    let entsize = if class == types::MACH_CLASS_64 { 16 } else { 12 };
+    if nsyms.checked_mul(entsize).and_then(|n| n.checked_add(symoff)).map_or(true, |end| end > file_len) {
+        return Err(Error::Truncated { offset: symoff });
+    }
+    if stroff.checked_add(strsize).map_or(true, |end| end > file_len) {
+        return Err(Error::Truncated { offset: stroff });
+    }
+    let mut strtab = vec![0u8; strsize as usize];
+    try!(r.seek(io::SeekFrom::Start(stroff)));
+    try!(r.read_exact(&mut strtab));
+
+    try!(r.seek(io::SeekFrom::Start(symoff)));
+    let mut symbols = Vec::new();
+    for _ in 0..nsyms {
+        let strx = try!(read_u32!(data, r)) as usize;
+        let n_type = try!(read_u8!(r));
+        let n_sect = try!(read_u8!(r));
+        let n_desc = try!(read_u16!(data, r));
+        let n_value = read_addr!(class, data, r);
+        let name = match strtab.get(strx..) {
+            Some(rest) => {
+                let end = rest.iter().position(|x| *x == 0).unwrap_or(rest.len());
+                String::from_utf8_lossy(&rest[..end]).into_owned()
+            },
+            None => return Err(Error::from("symbol name index out of range")),
+        };
+        symbols.push(types::Nlist {
+            name: name,
+            n_type: n_type,
+            n_sect: n_sect,
+            n_desc: n_desc,
+            n_value: n_value,
+        });
+    }
+    Ok(symbols)
+}
+
 pub struct File {
     hdr: types::FileHeader,
+    segments: Vec<types::SegmentCommand>,
     sections: HashMap<String, Section>,
+    symbols: Vec<types::Nlist>,
+    libraries: Vec<(String, types::DylibKind)>,
+    entry: u64,
 }
 
 impl File {
     #[allow(unused_variables, unused_assignments)]
-    pub fn parse<R: io::Read + io::Seek>(r: &mut R) -> Result<File, Box<error::Error>> {
+    pub fn parse<R: io::Read + io::Seek>(r: &mut R) -> Result<File, Error> {
+        let file_len = try!(r.seek(io::SeekFrom::End(0)));
         try!(r.seek(io::SeekFrom::Start(0)));
         let ident: u64 = 0;
         let mut data = types::Data(2);
@@ -93,81 +175,147 @@ impl File {
                 data = types::Data(1);
                 class = types::Class(2);
             },
-            _ => try!(Err(Error::from("invalid magic number"))),
+            _ => try!(Err(Error::BadMagic)),
         }
 
         let cputype = types::Machine(try!(read_i32!(data, r)));
 
-        let cpu_subtype = try!(read_u32!(data, r)) ^ 0x80000000;
+        let cpu_subtype = try!(read_u32!(data, r)) & !types::CPU_SUBTYPE_MASK;
         let file_type = try!(read_u32!(data, r));
         let ncmds = try!(read_u32!(data, r));
         let sizeof_cmds = try!(read_u32!(data, r));
         let flags = try!(read_u32!(data, r));
-        try!(read_u32!(data, r));
+        if class == types::MACH_CLASS_64 {
+            // reserved
+            try!(read_u32!(data, r));
+        }
 
         let mut cmds = Vec::new();
+        let mut segments = Vec::new();
         let mut sections = HashMap::new();
+        let mut main_off = None;
+        let mut thread_entry = None;
+        let mut symtab = None;
+        let mut libraries = Vec::new();
 
-        let mut cur_point = 0;
         for _ in 0..ncmds {
+            let cmd_start = try!(r.seek(io::SeekFrom::Current(0)));
             let cmd = try!(read_u32!(data, r));
             let size = try!(read_u32!(data, r));
-            cur_point += 8;
-            if cmd == 0x19 {
-                //Read LC_SECTION
-                let mut name_buf = [0u8; 16];
-                try!(r.read(&mut name_buf));
-                cur_point += 16;
-                let mut seg_name = String::from_utf8(name_buf.to_vec()).unwrap();
-                seg_name = String::from(seg_name.trim_matches('\0'));
-                if seg_name != "__PAGEZERO" {
-                    let mut i = 0;
-                    let vm_addr = try!(read_u64!(data, r));
-                    let vm_size = try!(read_u64!(data, r));
-                    let file_off = try!(read_u64!(data, r));
-                    let filesize = try!(read_u64!(data, r));
+            if size < 8 || cmd_start + size as u64 > file_len {
+                return Err(Error::Truncated { offset: cmd_start });
+            }
+            match cmd {
+                types::LC_SEGMENT | types::LC_SEGMENT_64 => {
+                    let seg_name = try!(read_name(r));
+                    let vm_addr = read_addr!(class, data, r);
+                    let vm_size = read_addr!(class, data, r);
+                    let file_off = read_addr!(class, data, r);
+                    let filesize = read_addr!(class, data, r);
                     let maxprot = try!(read_i32!(data, r));
                     let initprot = try!(read_i32!(data, r));
                     let nsects = try!(read_u32!(data, r));
                     let seg_flags = try!(read_u32!(data, r));
-                    cur_point += 48;
-                    while i < nsects {
+                    for _ in 0..nsects {
                         //Read Section
-                        try!(r.read(&mut name_buf));
-                        let mut sect_name = String::from_utf8(name_buf.to_vec()).unwrap();
-                        sect_name = String::from(sect_name.trim_matches('\0'));
-                        try!(r.read(&mut name_buf));
-                        cur_point += 32;
-                        let mut sect_seg_name = String::from_utf8(name_buf.to_vec()).unwrap();
-                        sect_seg_name = String::from(sect_seg_name.trim_matches('\0'));
-
-                        let addr = try!(read_u64!(data, r));
-                        let size = try!(read_u64!(data, r));
+                        let sect_name = try!(read_name(r));
+                        let sect_seg_name = try!(read_name(r));
+
+                        let addr = read_addr!(class, data, r);
+                        let size = read_addr!(class, data, r);
                         let offset = try!(read_u32!(data, r));
                         let align = try!(read_u32!(data, r));
                         let reloff = try!(read_u32!(data, r));
                         let nreloc = try!(read_u32!(data, r));
                         let flags = try!(read_u32!(data, r));
 
-                        let _ = try!(read_u64!(data, r));
+                        // reserved1, reserved2 (and reserved3 for 64-bit)
                         let _ = try!(read_u32!(data, r));
+                        let _ = try!(read_u32!(data, r));
+                        if class == types::MACH_CLASS_64 {
+                            let _ = try!(read_u32!(data, r));
+                        }
 
-                        cur_point += 48;
+                        let mut sect_flags = elf::types::SHF_ALLOC.0;
+                        if initprot & types::VM_PROT_WRITE != 0 {
+                            sect_flags |= elf::types::SHF_WRITE.0;
+                        }
+                        if flags & (types::S_ATTR_PURE_INSTRUCTIONS | types::S_ATTR_SOME_INSTRUCTIONS) != 0 {
+                            sect_flags |= elf::types::SHF_EXECINSTR.0;
+                        }
+                        let nobits = match flags & types::SECTION_TYPE {
+                            types::S_ZEROFILL | types::S_GB_ZEROFILL | types::S_THREAD_LOCAL_ZEROFILL => true,
+                            _ => false,
+                        };
 
                         let t_sect = Section {
                             name: sect_name,
+                            index: sections.len(),
                             addr: addr,
                             offset: offset as u64,
                             size: size,
+                            flags: elf::types::SectionFlag(sect_flags),
+                            shtype: if nobits { elf::types::SHT_NOBITS } else { elf::types::SHT_PROGBITS },
+                            link: 0,
+                            info: 0,
+                            entsize: 0,
+                            addralign: 1 << align,
+                            nobits: nobits,
+                            compressed_size: None,
+                            loaded: true,
                             data: Vec::new(),
                         };
                         sections.insert(t_sect.name.clone(), t_sect);
-                        i += 1;
                     }
-                }
+                    segments.push(types::SegmentCommand {
+                        name: seg_name,
+                        vm_addr: vm_addr,
+                        vm_size: vm_size,
+                        file_off: file_off,
+                        file_size: filesize,
+                        maxprot: maxprot,
+                        initprot: initprot,
+                        nsects: nsects,
+                        flags: seg_flags,
+                    });
+                },
+                types::LC_MAIN => {
+                    main_off = Some(try!(read_u64!(data, r)));
+                },
+                types::LC_UNIXTHREAD => {
+                    thread_entry = try!(thread_pc(data, cputype, r));
+                },
+                types::LC_SYMTAB => {
+                    let symoff = try!(read_u32!(data, r)) as u64;
+                    let nsyms = try!(read_u32!(data, r)) as u64;
+                    let stroff = try!(read_u32!(data, r)) as u64;
+                    let strsize = try!(read_u32!(data, r)) as u64;
+                    symtab = Some((symoff, nsyms, stroff, strsize));
+                },
+                types::LC_LOAD_DYLIB | types::LC_LOAD_WEAK_DYLIB | types::LC_REEXPORT_DYLIB |
+                types::LC_LAZY_LOAD_DYLIB | types::LC_LOAD_UPWARD_DYLIB => {
+                    let kind = match cmd {
+                        types::LC_LOAD_WEAK_DYLIB => types::DylibKind::Weak,
+                        types::LC_REEXPORT_DYLIB => types::DylibKind::Reexport,
+                        types::LC_LAZY_LOAD_DYLIB => types::DylibKind::Lazy,
+                        types::LC_LOAD_UPWARD_DYLIB => types::DylibKind::Upward,
+                        _ => types::DylibKind::Normal,
+                    };
+                    // The name is stored within the command, at an offset
+                    // from its start
+                    let name_off = try!(read_u32!(data, r));
+                    if name_off < 24 || name_off >= size {
+                        return Err(Error::from("dylib name offset out of range"));
+                    }
+                    let mut name = vec![0u8; (size - name_off) as usize];
+                    try!(r.seek(io::SeekFrom::Start(cmd_start + name_off as u64)));
+                    try!(r.read_exact(&mut name));
+                    let end = name.iter().position(|x| *x == 0).unwrap_or(name.len());
+                    libraries.push((String::from_utf8_lossy(&name[..end]).into_owned(), kind));
+                },
+                _ => {},
             }
-            try!(r.seek(io::SeekFrom::Current((size - cur_point) as i64)));
-            cur_point = 0;
+            try!(r.seek(io::SeekFrom::Start(cmd_start + size as u64)));
             let t_cmd = types::LoadCommand {
                 cmd: cmd,
                 size: size,
@@ -175,12 +323,29 @@ impl File {
             cmds.push(t_cmd);
         }
 
-        let t_size = sections.get("__text").unwrap().size;
-        let mut data_buf = Vec::with_capacity(t_size as usize);
-        unsafe { data_buf.set_len(t_size as usize); }
-        try!(r.seek(io::SeekFrom::Start(sections.get("__text").unwrap().offset as u64)));
-        try!(r.read(&mut data_buf));
-        sections.get_mut("__text").unwrap().data = data_buf;
+        for sect in sections.values_mut() {
+            if sect.nobits || sect.size == 0 {
+                continue;
+            }
+            if sect.offset.checked_add(sect.size).map_or(true, |end| end > file_len) {
+                return Err(Error::Truncated { offset: sect.offset });
+            }
+            try!(r.seek(io::SeekFrom::Start(sect.offset)));
+            sect.data = try!(io::Read::by_ref(r).bytes().take(sect.size as usize).collect());
+        }
+
+        let symbols = match symtab {
+            Some(symtab) => try!(read_symbols(r, class, data, file_len, symtab)),
+            None => Vec::new(),
+        };
+
+        // LC_MAIN holds a file offset; the entry point is where __TEXT maps it
+        let entry = match main_off {
+            Some(off) => segments.iter()
+                .find(|s| s.name == "__TEXT")
+                .map_or(off, |s| s.vm_addr + off - s.file_off),
+            None => thread_entry.unwrap_or(0),
+        };
 
         let x = File {
             hdr: types::FileHeader {
@@ -193,16 +358,64 @@ impl File {
                 flags: flags,
                 data: data,
             },
+            segments: segments,
             sections: sections,
+            symbols: symbols,
+            libraries: libraries,
+            entry: entry,
         };
         Ok(x)
     }
+    /// Segments from the LC_SEGMENT/LC_SEGMENT_64 load commands, in file order
+    pub fn segments(&self) -> &[types::SegmentCommand] {
+        &self.segments
+    }
     pub fn sections(&self) -> &HashMap<String, Section> {
         &self.sections
     }
+    /// Install names of the dylibs this file links against, with how each
+    /// is linked, in load command order
+    pub fn linked_libraries(&self) -> Vec<(String, types::DylibKind)> {
+        self.libraries.clone()
+    }
+    /// Entries of the `LC_SYMTAB` symbol table, stabs included, in table order
+    pub fn symbol_table(&self) -> &[types::Nlist] {
+        &self.symbols
+    }
+    /// Sections in section table order
+    pub fn iter_sections(&self) -> impl Iterator<Item = &Section> {
+        let mut sections: Vec<&Section> = self.sections.values().collect();
+        sections.sort_by_key(|s| s.index);
+        sections.into_iter()
+    }
 }
 
 impl Object for File {
+    fn format(&self) -> ::Format {
+        ::Format::MachO
+    }
+    /// Symbols of `symbol_table()`, without the stabs; Mach-O records no
+    /// sizes, and a section's instruction attributes decide function vs data
+    fn symbols(&self) -> Vec<::Symbol> {
+        self.symbols.iter().filter(|s| !s.is_stab()).map(|s| {
+            let kind = match s.symbol_type() {
+                types::N_SECT => match self.sections.values().find(|sect| sect.index + 1 == s.n_sect as usize) {
+                    Some(sect) if sect.is_executable() => ::SymbolKind::Function,
+                    Some(_) => ::SymbolKind::Data,
+                    None => ::SymbolKind::Unknown,
+                },
+                _ => ::SymbolKind::Unknown,
+            };
+            ::Symbol {
+                name: s.name.clone(),
+                address: s.n_value,
+                size: 0,
+                kind: kind,
+                global: s.is_external(),
+                defined: s.symbol_type() != types::N_UNDF,
+            }
+        }).collect()
+    }
     fn arch(&self) -> ::Arch {
         let endian = match self.hdr.data {
             types::MACH_DATA_2LSB => ::Endianness::Little,
@@ -212,6 +425,15 @@ impl Object for File {
         match self.hdr.cpu_type {
             types::PM_I386 => ::Arch::X86(::Width::W32),
             types::PM_X86_64 => ::Arch::X86(::Width::W64),
+            types::PM_ARM => {
+                let arm_type = match self.hdr.cpu_subtype {
+                    types::CPU_SUBTYPE_ARM_V6M | types::CPU_SUBTYPE_ARM_V7M |
+                    types::CPU_SUBTYPE_ARM_V7EM => ::ARMType::MClass,
+                    _ => ::ARMType::ARM,
+                };
+                ::Arch::ARM(::Width::W32, endian, ::ARMMode::ARM, arm_type)
+            },
+            types::PM_ARM64 => ::Arch::ARM(::Width::W64, endian, ::ARMMode::ARM, ::ARMType::V8),
             types::PM_POWERPC => ::Arch::PPC(::Width::W32, endian),
             types::PM_POWERPC64 => ::Arch::PPC(::Width::W64, endian),
             _ => return ::Arch::Unknown,
@@ -222,4 +444,28 @@ impl Object for File {
         fmt_name = String::from("__") + &fmt_name;
         self.sections.get(fmt_name.as_str())
     }
+    fn entry(&self) -> u64 {
+        self.entry
+    }
+    fn obj_type(&self) -> ::ObjectType {
+        match self.hdr.file_type {
+            types::MH_OBJECT => ::ObjectType::Relocatable,
+            types::MH_EXECUTE => ::ObjectType::Executable,
+            types::MH_DYLIB | types::MH_BUNDLE => ::ObjectType::SharedObject,
+            types::MH_CORE => ::ObjectType::Core,
+            _ => ::ObjectType::Unknown,
+        }
+    }
+    fn endianness(&self) -> ::Endianness {
+        match self.hdr.data {
+            types::MACH_DATA_2MSB => ::Endianness::Big,
+            _ => ::Endianness::Little,
+        }
+    }
+    fn width(&self) -> ::Width {
+        match self.hdr.magic {
+            types::MACH64_HDR_MAG | types::MACH64_HDR_CIG => ::Width::W64,
+            _ => ::Width::W32,
+        }
+    }
 }
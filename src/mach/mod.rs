@@ -1,4 +1,6 @@
+pub mod fat;
 pub mod file;
 pub mod types;
 
+pub use mach::fat::FatFile;
 pub use mach::file::File;
@@ -36,13 +36,178 @@ impl SectionHeader {
     }
 }
 
+/// A segment described by an `LC_SEGMENT`/`LC_SEGMENT_64` load command
+#[derive(Clone, Debug)]
+pub struct SegmentCommand {
+    pub name: String,
+    pub vm_addr: u64,
+    pub vm_size: u64,
+    pub file_off: u64,
+    pub file_size: u64,
+    pub maxprot: i32,
+    pub initprot: i32,
+    pub nsects: u32,
+    pub flags: u32,
+}
+
+/// How a dylib load command links its library
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DylibKind {
+    /// `LC_LOAD_DYLIB`
+    Normal,
+    /// `LC_LOAD_WEAK_DYLIB`
+    Weak,
+    /// `LC_REEXPORT_DYLIB`
+    Reexport,
+    /// `LC_LAZY_LOAD_DYLIB`
+    Lazy,
+    /// `LC_LOAD_UPWARD_DYLIB`
+    Upward,
+}
+
+/// Symbol table entry (`struct nlist`/`nlist_64`)
+#[derive(Clone, Debug)]
+pub struct Nlist {
+    pub name: String,
+    pub n_type: u8,
+    /// 1-based section index, or `NO_SECT`
+    pub n_sect: u8,
+    pub n_desc: u16,
+    pub n_value: u64,
+}
+
+impl Nlist {
+    /// Debugging (stab) entry rather than a symbol
+    pub fn is_stab(&self) -> bool {
+        self.n_type & N_STAB != 0
+    }
+    pub fn is_external(&self) -> bool {
+        self.n_type & N_EXT != 0
+    }
+    /// `N_UNDF`, `N_ABS`, `N_SECT`, `N_PBUD` or `N_INDR`
+    pub fn symbol_type(&self) -> u8 {
+        self.n_type & N_TYPE
+    }
+}
+
+/// Mask for the stab bits of `n_type`
+pub const N_STAB: u8 = 0xe0;
+/// Private external (visibility hidden) bit of `n_type`
+pub const N_PEXT: u8 = 0x10;
+/// Mask for the type bits of `n_type`
+pub const N_TYPE: u8 = 0x0e;
+/// External symbol bit of `n_type`
+pub const N_EXT: u8 = 0x01;
+/// Undefined symbol
+pub const N_UNDF: u8 = 0x0;
+/// Absolute symbol
+pub const N_ABS: u8 = 0x2;
+/// Symbol defined in section `n_sect`
+pub const N_SECT: u8 = 0xe;
+/// Prebound undefined symbol
+pub const N_PBUD: u8 = 0xc;
+/// Indirect symbol, an alias of the symbol named by `n_value`
+pub const N_INDR: u8 = 0xa;
+/// `n_sect` of a symbol in no section
+pub const NO_SECT: u8 = 0;
+
+/// 32-bit segment of this file to be mapped
+pub const LC_SEGMENT: u32 = 0x1;
+/// Link-edit stab symbol table info
+pub const LC_SYMTAB: u32 = 0x2;
+/// Dynamically linked shared library
+pub const LC_LOAD_DYLIB: u32 = 0xc;
+/// Shared library that may be missing at run time
+pub const LC_LOAD_WEAK_DYLIB: u32 = 0x80000018;
+/// Shared library whose symbols are re-exported as this file's own
+pub const LC_REEXPORT_DYLIB: u32 = 0x8000001f;
+/// Shared library loaded on first use
+pub const LC_LAZY_LOAD_DYLIB: u32 = 0x20;
+/// Shared library that may in turn depend on this one
+pub const LC_LOAD_UPWARD_DYLIB: u32 = 0x80000023;
+/// Unix thread (includes a stack)
+pub const LC_UNIXTHREAD: u32 = 0x5;
+/// 64-bit segment of this file to be mapped
+pub const LC_SEGMENT_64: u32 = 0x19;
+/// Replacement for LC_UNIXTHREAD
+pub const LC_MAIN: u32 = 0x80000028;
+
+/// Mask for the section type in section flags
+pub const SECTION_TYPE: u32 = 0x000000ff;
+/// Zero fill on demand section
+pub const S_ZEROFILL: u32 = 0x1;
+/// Zero fill on demand section that can be larger than 4 gigabytes
+pub const S_GB_ZEROFILL: u32 = 0xc;
+/// Zero fill on demand thread local variables
+pub const S_THREAD_LOCAL_ZEROFILL: u32 = 0x12;
+
+/// Mask for the capability bits in the cpu subtype
+pub const CPU_SUBTYPE_MASK: u32 = 0xff000000;
+/// ARMv8 (AArch32)
+pub const CPU_SUBTYPE_ARM_V8: u32 = 13;
+/// ARMv6-M
+pub const CPU_SUBTYPE_ARM_V6M: u32 = 14;
+/// ARMv7-M
+pub const CPU_SUBTYPE_ARM_V7M: u32 = 15;
+/// ARMv7E-M
+pub const CPU_SUBTYPE_ARM_V7EM: u32 = 16;
+
+/// i386 thread state flavor
+pub const X86_THREAD_STATE32: u32 = 1;
+/// x86_64 thread state flavor
+pub const X86_THREAD_STATE64: u32 = 4;
+/// 32-bit ARM thread state flavor
+pub const ARM_THREAD_STATE: u32 = 1;
+/// AArch64 thread state flavor
+pub const ARM_THREAD_STATE64: u32 = 6;
+
+/// Relocatable object file
+pub const MH_OBJECT: u32 = 0x1;
+/// Demand paged executable file
+pub const MH_EXECUTE: u32 = 0x2;
+/// Core file
+pub const MH_CORE: u32 = 0x4;
+/// Dynamically bound shared library
+pub const MH_DYLIB: u32 = 0x6;
+/// Dynamically bound bundle file
+pub const MH_BUNDLE: u32 = 0x8;
+
+/// Section contains only machine instructions
+pub const S_ATTR_PURE_INSTRUCTIONS: u32 = 0x80000000;
+/// Section contains some machine instructions
+pub const S_ATTR_SOME_INSTRUCTIONS: u32 = 0x00000400;
+
+/// Segment is readable
+pub const VM_PROT_READ: i32 = 0x1;
+/// Segment is writable
+pub const VM_PROT_WRITE: i32 = 0x2;
+/// Segment is executable
+pub const VM_PROT_EXECUTE: i32 = 0x4;
+
 pub const NIDENT: usize = 4;
 
 pub const MACH_HDR_MAG: u32 = 0xFEEDFACE;
 pub const MACH64_HDR_MAG: u32 = 0xFEEDFACF;
 pub const MACH_HDR_CIG: u32 = 0xCEFAEDFE;
 pub const MACH64_HDR_CIG: u32 = 0xCFFAEDFE;
+/// Universal (fat) binary header, always big-endian on disk
+pub const FAT_MAGIC: u32 = 0xCAFEBABE;
+/// Universal (fat) binary header with 64-bit offsets
+pub const FAT_MAGIC_64: u32 = 0xCAFEBABF;
+/// Upper bound on nfat_arch, to tell fat binaries from Java class files
+pub const FAT_MAX_ARCH: u32 = 45;
+
+/// One entry of a fat binary's fat_arch table
+#[derive(Clone, Copy, Debug)]
+pub struct FatArch {
+    pub cpu_type: Machine,
+    pub cpu_subtype: u32,
+    pub offset: u64,
+    pub size: u64,
+    pub align: u32,
+}
 
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub struct Class(pub u8);
 pub const MACH_CLASS_NONE: Class = Class(0);
 pub const MACH_CLASS_32: Class = Class(1);
@@ -66,6 +231,7 @@ impl fmt::Display for Class {
     }
 }
 
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub struct Data(pub u8);
 pub const MACH_DATA_NONE: Data = Data(0);
 pub const MACH_DATA_2LSB: Data = Data(1);
@@ -91,6 +257,7 @@ impl fmt::Display for Data {
 
 pub const ABI64: i32 = 0x01000000;
 
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub struct Machine(pub i32);
 pub const PM_ANY: Machine = Machine(-1);
 pub const PM_VAX: Machine = Machine(1);
@@ -100,6 +267,8 @@ pub const PM_I386: Machine = PM_X86;
 pub const PM_X86_64: Machine = Machine(7 | ABI64);
 pub const PM_MC98000: Machine = Machine(10);
 pub const PM_HPPA: Machine = Machine(11);
+pub const PM_ARM: Machine = Machine(12);
+pub const PM_ARM64: Machine = Machine(12 | ABI64);
 pub const PM_MC88000: Machine = Machine(13);
 pub const PM_SPARC: Machine = Machine(14);
 pub const PM_I860: Machine = Machine(15);
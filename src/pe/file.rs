@@ -2,11 +2,12 @@ use std::io::prelude::*;
 use std::io;
 use std::ffi;
 use std::fmt;
-use std::error;
+use std::cmp;
 use byteorder;
 use byteorder::ReadBytesExt;
 use pe::types;
 use std::collections::HashMap;
+use elf;
 use {Error, Object, Section};
 
 macro_rules! read_u8 {
@@ -33,20 +34,84 @@ macro_rules! read_u64 {
     );
 }
 
+fn le_u32(b: &[u8]) -> u32 {
+    b[0] as u32 | (b[1] as u32) << 8 | (b[2] as u32) << 16 | (b[3] as u32) << 24
+}
+
+/// Read `count` COFF symbol records at `offset` along with the string table
+/// that follows them
+fn read_coff_symbols<R: io::Read + io::Seek>(r: &mut R, offset: u64, count: u64, file_len: u64) -> Result<Vec<types::Symbol>, Error> {
+    let strtab_off = match count.checked_mul(types::IMAGE_SIZEOF_SYMBOL).and_then(|n| n.checked_add(offset)) {
+        Some(end) if end <= file_len => end,
+        _ => return Err(Error::Truncated { offset: offset }),
+    };
+    let mut records = vec![0u8; (strtab_off - offset) as usize];
+    try!(r.seek(io::SeekFrom::Start(offset)));
+    try!(r.read_exact(&mut records));
+
+    // The string table's size field counts itself; it may be missing
+    // entirely if no name needs it
+    let mut strtab = Vec::new();
+    if strtab_off + 4 <= file_len {
+        let size = try!(read_u32!(r)) as u64;
+        if size > 4 && strtab_off + size <= file_len {
+            strtab = vec![0u8; size as usize];
+            try!(r.read_exact(&mut strtab[4..]));
+        }
+    }
+    let cstr = |bytes: &[u8]| {
+        let end = bytes.iter().position(|x| *x == 0).unwrap_or(bytes.len());
+        ffi::CString::new(&bytes[..end]).unwrap()
+    };
+
+    let size = types::IMAGE_SIZEOF_SYMBOL as usize;
+    let mut symbols = Vec::new();
+    let mut i = 0;
+    while i < count as usize {
+        let rec = &records[i * size..(i + 1) * size];
+        let num_aux_sym = rec[17];
+        let storage_class = rec[16];
+        let aux_end = cmp::min(count as usize, i + 1 + num_aux_sym as usize);
+        let name = if storage_class == types::IMAGE_SYM_CLASS_FILE {
+            cstr(&records[(i + 1) * size..aux_end * size])
+        } else if rec[0..4] == [0, 0, 0, 0] {
+            let off = (rec[4] as usize) | (rec[5] as usize) << 8 | (rec[6] as usize) << 16 | (rec[7] as usize) << 24;
+            match strtab.get(off..) {
+                Some(rest) if off >= 4 => cstr(rest),
+                _ => return Err(Error::from("symbol name index out of range")),
+            }
+        } else {
+            cstr(&rec[0..8])
+        };
+        symbols.push(types::Symbol {
+            name: name,
+            value: rec[8] as u32 | (rec[9] as u32) << 8 | (rec[10] as u32) << 16 | (rec[11] as u32) << 24,
+            sect_no: rec[12] as u16 | (rec[13] as u16) << 8,
+            sym_type: rec[14] as u16 | (rec[15] as u16) << 8,
+            storage_class: storage_class,
+            num_aux_sym: num_aux_sym,
+        });
+        i = aux_end;
+    }
+    Ok(symbols)
+}
+
 pub struct File {
     pub file_hdr: types::FileHeader,
     pub opt_hdr: types::OptionalHeader,
     pub sections: HashMap<String, Section>,
+    /// COFF symbol table, which images only keep when not stripped
+    pub symbols: Vec<types::Symbol>,
 }
 
 impl File {
     #[allow(unused_variables,unused_assignments)]
-    pub fn parse<R: io::Read + io::Seek>(r: &mut R) -> Result<File, Box<error::Error>> {
+    pub fn parse<R: io::Read + io::Seek>(r: &mut R) -> Result<File, Error> {
         try!(r.seek(io::SeekFrom::Start(0)));
         let dossig = try!(read_u16!(r));
 
         if dossig != types::DOS_HDR_MAG {
-            try!(Err(Error::from("invalid DOS signature")));
+            try!(Err(Error::BadMagic));
         }
 
         try!(r.seek(io::SeekFrom::Start(0x3c)));
@@ -58,7 +123,7 @@ impl File {
         let pesig = try!(read_u32!(r));
 
         if pesig != types::PE_HDR_MAG {
-            try!(Err(Error::from("invalid PE signature")));
+            try!(Err(Error::BadMagic));
         }
 
         let machine = types::Machine(try!(read_u16!(r)));
@@ -140,6 +205,17 @@ impl File {
         let loader_flags = try!(read_u32!(r));
         let num_rva = try!(read_u32!(r));
 
+        // Only as many directories as fit in the optional header are real
+        let fixed_size = if magic == types::PECLASS64 { 112 } else { 96 };
+        let fits = (opt_hdr_size as usize).saturating_sub(fixed_size) / 8;
+        let mut data_dirs = Vec::new();
+        for _ in 0..cmp::min(cmp::min(num_rva as usize, fits), types::IMAGE_NUMBEROF_DIRECTORY_ENTRIES) {
+            let rva = try!(read_u32!(r));
+            let size = try!(read_u32!(r));
+            data_dirs.push(types::DataDirectory { rva: rva, size: size });
+        }
+
+        let file_len = try!(r.seek(io::SeekFrom::End(0)));
         try!(r.seek(io::SeekFrom::Start((foff as u64+opt_hdr_size as u64+0x18))));
 
         let mut sections_lst = Vec::new();
@@ -148,7 +224,7 @@ impl File {
         for _ in 0..num_sections {
             let mut name = [0u8; 8];
 
-            try!(r.read(&mut name));
+            try!(r.read_exact(&mut name));
 
             let mut name_str = name.as_ref();
 
@@ -181,19 +257,55 @@ impl File {
             });
         }
 
-        for shdr in sections_lst.into_iter() {
-            try!(r.seek(io::SeekFrom::Start(shdr.raw_ptr as u64)));
-            let data: Vec<u8> = io::Read::by_ref(r).bytes().map(|x| x.unwrap()).take(shdr.virt_size as usize).collect();
-            let name = String::from_utf8(shdr.name.as_bytes().to_vec()).unwrap();
+        for (i, shdr) in sections_lst.into_iter().enumerate() {
+            let mut flags = elf::types::SHF_ALLOC.0;
+            if shdr.characteristics & types::IMAGE_SCN_MEM_WRITE != 0 {
+                flags |= elf::types::SHF_WRITE.0;
+            }
+            if shdr.characteristics & (types::IMAGE_SCN_MEM_EXECUTE | types::IMAGE_SCN_CNT_CODE) != 0 {
+                flags |= elf::types::SHF_EXECINSTR.0;
+            }
+            // SizeOfRawData is rounded up to the file alignment and may exceed
+            // the in-memory size; a zero VirtualSize means use the raw size
+            let size = if shdr.virt_size == 0 { shdr.data_size } else { shdr.virt_size };
+            let nobits = shdr.raw_ptr == 0 || shdr.data_size == 0;
+            let align = (shdr.characteristics & types::IMAGE_SCN_ALIGN_MASK) >> 20;
+            let data: Vec<u8> = if nobits {
+                Vec::new()
+            } else {
+                let len = size.min(shdr.data_size) as u64;
+                if shdr.raw_ptr as u64 + len > file_len {
+                    return Err(Error::Truncated { offset: shdr.raw_ptr as u64 });
+                }
+                try!(r.seek(io::SeekFrom::Start(shdr.raw_ptr as u64)));
+                try!(io::Read::by_ref(r).bytes().take(len as usize).collect())
+            };
+            let name = String::from_utf8_lossy(shdr.name.as_bytes()).into_owned();
             sections.insert(name.clone(), Section {
                 name: name,
+                index: i,
                 addr: shdr.virt_addr,
-                offset: shdr.virt_addr - base_img,
-                size: shdr.virt_size as u64,
+                offset: shdr.raw_ptr as u64,
+                size: size as u64,
+                flags: elf::types::SectionFlag(flags),
+                shtype: if nobits { elf::types::SHT_NOBITS } else { elf::types::SHT_PROGBITS },
+                link: 0,
+                info: 0,
+                entsize: 0,
+                addralign: if align == 0 { 0 } else { 1 << (align - 1) },
+                nobits: nobits,
+                compressed_size: None,
+                loaded: true,
                 data: data,
             });
         }
 
+        let symbols = if sym_tab_ptr != 0 && num_sym != 0 {
+            try!(read_coff_symbols(r, sym_tab_ptr as u64, num_sym as u64, file_len))
+        } else {
+            Vec::new()
+        };
+
         Ok(File {
             file_hdr: types::FileHeader {
                 machine: machine,
@@ -235,14 +347,156 @@ impl File {
                 heap_commit_size: heap_commit_size,
                 loader_flags: loader_flags,
                 num_rva: num_rva,
+                data_dirs: data_dirs,
             },
             sections: sections,
+            symbols: symbols,
         })
     }
 
     pub fn sections(&self) -> &HashMap<String, Section> {
         &self.sections
     }
+    /// Section data from relative virtual address `rva` to the end of the
+    /// section containing it
+    fn rva_data(&self, rva: u32) -> Option<&[u8]> {
+        let addr = self.opt_hdr.base_img + rva as u64;
+        self.sections.values()
+            .find(|s| addr >= s.addr && addr - s.addr < s.data.len() as u64)
+            .map(|s| &s.data[(addr - s.addr) as usize..])
+    }
+    /// NUL-terminated string at `rva`
+    fn rva_string(&self, rva: u32) -> Option<String> {
+        self.rva_data(rva).map(|d| {
+            let end = d.iter().position(|x| *x == 0).unwrap_or(d.len());
+            String::from_utf8_lossy(&d[..end]).into_owned()
+        })
+    }
+    /// Modules and functions of the import directory, in table order
+    ///
+    /// Each module's functions come from its import lookup table, or from
+    /// its import address table when there is no lookup table (as with some
+    /// older linkers). A malformed entry ends the list early.
+    pub fn imports(&self) -> Vec<types::ImportModule> {
+        let mut modules = Vec::new();
+        let dir = match self.opt_hdr.data_dirs.get(types::IMAGE_DIRECTORY_ENTRY_IMPORT) {
+            Some(d) if d.rva != 0 => *d,
+            _ => return modules,
+        };
+        let wide = self.opt_hdr.magic == types::PECLASS64;
+        let (thunk_size, ordinal_flag) = if wide { (8, types::IMAGE_ORDINAL_FLAG64) } else { (4, types::IMAGE_ORDINAL_FLAG32) };
+        let mut desc_rva = dir.rva;
+        loop {
+            let desc = match self.rva_data(desc_rva) {
+                Some(d) if d.len() >= 20 => d,
+                _ => break,
+            };
+            let field = |i: usize| le_u32(&desc[i * 4..]);
+            let (ilt, name_rva, iat) = (field(0), field(3), field(4));
+            if ilt == 0 && name_rva == 0 && iat == 0 {
+                break;
+            }
+            let name = match self.rva_string(name_rva) {
+                Some(n) => n,
+                None => break,
+            };
+            let mut functions = Vec::new();
+            let mut thunk_rva = if ilt != 0 { ilt } else { iat };
+            while let Some(t) = self.rva_data(thunk_rva) {
+                if t.len() < thunk_size {
+                    break;
+                }
+                let thunk = if wide { le_u32(t) as u64 | (le_u32(&t[4..]) as u64) << 32 } else { le_u32(t) as u64 };
+                if thunk == 0 {
+                    break;
+                }
+                if thunk & ordinal_flag != 0 {
+                    functions.push(types::ImportedFunction::Ordinal(thunk as u16));
+                } else {
+                    // Hint/name entry: a 16-bit hint, then the name
+                    match self.rva_data(thunk as u32) {
+                        Some(h) if h.len() >= 2 => {
+                            let end = h[2..].iter().position(|x| *x == 0).map_or(h.len(), |n| n + 2);
+                            functions.push(types::ImportedFunction::Named {
+                                hint: h[0] as u16 | (h[1] as u16) << 8,
+                                name: String::from_utf8_lossy(&h[2..end]).into_owned(),
+                            });
+                        },
+                        _ => break,
+                    }
+                }
+                thunk_rva = match thunk_rva.checked_add(thunk_size as u32) {
+                    Some(rva) => rva,
+                    None => break,
+                };
+            }
+            modules.push(types::ImportModule { name: name, functions: functions });
+            desc_rva = match desc_rva.checked_add(20) {
+                Some(rva) => rva,
+                None => break,
+            };
+        }
+        modules
+    }
+    /// Entries of the export directory, in ordinal order
+    ///
+    /// Unused slots of the export address table are skipped. An export whose
+    /// address lies inside the export directory is a forwarder, the address
+    /// being that of the forward target string.
+    pub fn exports(&self) -> Vec<types::ExportedSymbol> {
+        let mut exports = Vec::new();
+        let dir = match self.opt_hdr.data_dirs.get(types::IMAGE_DIRECTORY_ENTRY_EXPORT) {
+            Some(d) if d.rva != 0 => *d,
+            _ => return exports,
+        };
+        let hdr = match self.rva_data(dir.rva) {
+            Some(d) if d.len() >= 40 => d,
+            _ => return exports,
+        };
+        let field = |i: usize| le_u32(&hdr[i * 4..]);
+        let (base, num_funcs, num_names) = (field(4), field(5), field(6));
+        let (funcs_rva, names_rva, ordinals_rva) = (field(7), field(8), field(9));
+
+        // Names are keyed by their unbiased index into the address table
+        let mut names = HashMap::new();
+        if let (Some(name_ptrs), Some(ordinals)) = (self.rva_data(names_rva), self.rva_data(ordinals_rva)) {
+            let count = cmp::min(num_names as usize, cmp::min(name_ptrs.len() / 4, ordinals.len() / 2));
+            for i in 0..count {
+                let index = ordinals[i * 2] as u32 | (ordinals[i * 2 + 1] as u32) << 8;
+                if let Some(name) = self.rva_string(le_u32(&name_ptrs[i * 4..])) {
+                    names.insert(index, name);
+                }
+            }
+        }
+
+        let funcs = match self.rva_data(funcs_rva) {
+            Some(f) => f,
+            None => return exports,
+        };
+        for i in 0..cmp::min(num_funcs as usize, funcs.len() / 4) {
+            let rva = le_u32(&funcs[i * 4..]);
+            if rva == 0 {
+                continue;
+            }
+            let forwarded = rva >= dir.rva && rva - dir.rva < dir.size;
+            let target = match self.rva_string(rva) {
+                Some(fwd) if forwarded => types::ExportTarget::Forward(fwd),
+                _ => types::ExportTarget::Rva(rva),
+            };
+            exports.push(types::ExportedSymbol {
+                name: names.get(&(i as u32)).cloned(),
+                ordinal: base.wrapping_add(i as u32),
+                target: target,
+            });
+        }
+        exports
+    }
+    /// Sections in section table order
+    pub fn iter_sections(&self) -> impl Iterator<Item = &Section> {
+        let mut sections: Vec<&Section> = self.sections.values().collect();
+        sections.sort_by_key(|s| s.index);
+        sections.into_iter()
+    }
 }
 
 impl fmt::Display for File {
@@ -252,11 +506,85 @@ impl fmt::Display for File {
 }
 
 impl Object for File {
+    fn format(&self) -> ::Format {
+        ::Format::Pe
+    }
+    /// Symbols of the COFF symbol table, with section-relative values
+    /// turned into addresses, followed by named exports the table lacks
+    /// (usually all of them in a release DLL); debugging entries and
+    /// forwarded exports are left out
+    fn symbols(&self) -> Vec<::Symbol> {
+        let sections: Vec<&Section> = self.iter_sections().collect();
+        let mut symbols: Vec<::Symbol> = self.symbols.iter().filter_map(|s| {
+            let sect_no = s.sect_no as i16;
+            if sect_no == types::IMAGE_SYM_DEBUG {
+                return None;
+            }
+            // Section numbers count from 1; sections with the same name
+            // share one map entry, so this can't be a position in `sections`
+            let section = if sect_no > 0 { sections.iter().find(|sect| sect.index + 1 == sect_no as usize) } else { None };
+            let kind = if s.storage_class == types::IMAGE_SYM_CLASS_FILE {
+                ::SymbolKind::File
+            } else if s.storage_class == types::IMAGE_SYM_CLASS_STATIC && s.value == 0 && s.num_aux_sym != 0 {
+                // Section definitions carry their details in an aux record
+                ::SymbolKind::Section
+            } else if s.sym_type >> 4 == types::IMAGE_SYM_DTYPE_FUNCTION {
+                ::SymbolKind::Function
+            } else {
+                match section {
+                    Some(sect) if sect.is_executable() => ::SymbolKind::Function,
+                    Some(_) => ::SymbolKind::Data,
+                    None => ::SymbolKind::Unknown,
+                }
+            };
+            Some(::Symbol {
+                name: String::from_utf8_lossy(s.name.as_bytes()).into_owned(),
+                address: section.map_or(s.value as u64, |sect| sect.addr.wrapping_add(s.value as u64)),
+                size: 0,
+                kind: kind,
+                global: s.storage_class == types::IMAGE_SYM_CLASS_EXTERNAL || s.storage_class == types::IMAGE_SYM_CLASS_WEAK_EXTERNAL,
+                defined: sect_no != 0,
+            })
+        }).collect();
+
+        // Named exports, unless the symbol table already has them
+        for export in self.exports() {
+            let (name, rva) = match export {
+                types::ExportedSymbol { name: Some(n), target: types::ExportTarget::Rva(rva), .. } => (n, rva),
+                _ => continue,
+            };
+            let address = self.opt_hdr.base_img.wrapping_add(rva as u64);
+            if symbols.iter().any(|s| s.name == name && s.address == address) {
+                continue;
+            }
+            let kind = match sections.iter().find(|s| address >= s.addr && address - s.addr < s.size) {
+                Some(sect) if sect.is_executable() => ::SymbolKind::Function,
+                Some(_) => ::SymbolKind::Data,
+                None => ::SymbolKind::Unknown,
+            };
+            symbols.push(::Symbol {
+                name: name,
+                address: address,
+                size: 0,
+                kind: kind,
+                global: true,
+                defined: true,
+            });
+        }
+        symbols
+    }
     fn arch(&self) -> ::Arch {
         match self.file_hdr.machine {
             types::PM_AMD6 => ::Arch::X86(::Width::W64),
             types::PM_I386 => ::Arch::X86(::Width::W32),
-            types::PM_ARM => ::Arch::ARM(::Width::W32, ::Endianness::Little, ::ARMMode::ARM, ::ARMType::ARM),
+            types::PM_ARM | types::PM_THUMB => ::Arch::ARM(::Width::W32, ::Endianness::Little, ::ARMMode::ARM, ::ARMType::ARM),
+            types::PM_ARMNT => ::Arch::ARM(::Width::W32, ::Endianness::Little, ::ARMMode::Thumb, ::ARMType::ARM),
+            types::PM_ARM64 => ::Arch::ARM(::Width::W64, ::Endianness::Little, ::ARMMode::ARM, ::ARMType::V8),
+            types::PM_POWERPC | types::PM_POWERPCFP => ::Arch::PPC(::Width::W32, ::Endianness::Little),
+            types::PM_R4000 | types::PM_MIPS16 | types::PM_MIPSFPU | types::PM_MIPSFPU16 |
+            types::PM_WCEMIPSSV2 => ::Arch::MIPS(::Width::W32, ::Endianness::Little),
+            types::PM_RISCV32 => ::Arch::RISCV(::Width::W32, ::Endianness::Little),
+            types::PM_RISCV64 => ::Arch::RISCV(::Width::W64, ::Endianness::Little),
             _ => ::Arch::Unknown,
 
         }
@@ -264,4 +592,26 @@ impl Object for File {
     fn get_section(&self, name: &str) -> Option<&Section> {
         self.sections.get(name)
     }
+    fn entry(&self) -> u64 {
+        self.opt_hdr.base_img + self.opt_hdr.enter_addr as u64
+    }
+    fn obj_type(&self) -> ::ObjectType {
+        if self.file_hdr.characteristics & types::IMAGE_FILE_DLL != 0 {
+            ::ObjectType::SharedObject
+        } else if self.file_hdr.characteristics & types::IMAGE_FILE_EXECUTABLE_IMAGE != 0 {
+            ::ObjectType::Executable
+        } else {
+            ::ObjectType::Relocatable
+        }
+    }
+    fn endianness(&self) -> ::Endianness {
+        ::Endianness::Little
+    }
+    fn width(&self) -> ::Width {
+        if self.opt_hdr.magic == types::PECLASS64 {
+            ::Width::W64
+        } else {
+            ::Width::W32
+        }
+    }
 }
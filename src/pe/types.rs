@@ -71,6 +71,10 @@ pub const PM_SH5: Machine = Machine(0x1a8);
 pub const PM_THUMB: Machine = Machine(0x1c2);
 /// MIPS little-endian WCE v2
 pub const PM_WCEMIPSSV2: Machine = Machine(0x169);
+/// RISC-V 32-bit address space
+pub const PM_RISCV32: Machine = Machine(0x5032);
+/// RISC-V 64-bit address space
+pub const PM_RISCV64: Machine = Machine(0x5064);
 
 impl fmt::Debug for Machine {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -103,12 +107,47 @@ impl fmt::Display for Machine {
             PM_SH5 => "Hitachi SH5",
             PM_THUMB => "ARM or Thumb (\"interworking\") ",
             PM_WCEMIPSSV2 => "MIPS little-endian WCE v2",
+            PM_RISCV32 => "RISC-V 32-bit address space",
+            PM_RISCV64 => "RISC-V 64-bit address space",
             _ => "unknown",
         };
         write!(f, "{}", str)
     }
 }
 
+/// Image is valid and can be run
+pub const IMAGE_FILE_EXECUTABLE_IMAGE: u16 = 0x0002;
+/// Image is a dynamic-link library
+pub const IMAGE_FILE_DLL: u16 = 0x2000;
+
+/// Section contains executable code
+pub const IMAGE_SCN_CNT_CODE: u32 = 0x00000020;
+/// Section can be executed as code
+pub const IMAGE_SCN_MEM_EXECUTE: u32 = 0x20000000;
+/// Section can be read
+pub const IMAGE_SCN_MEM_READ: u32 = 0x40000000;
+/// Section can be written to
+pub const IMAGE_SCN_MEM_WRITE: u32 = 0x80000000;
+/// Alignment of the section's data (object files only), as log2 + 1
+pub const IMAGE_SCN_ALIGN_MASK: u32 = 0x00F00000;
+
+/// Size of a COFF symbol table record
+pub const IMAGE_SIZEOF_SYMBOL: u64 = 18;
+/// Symbol section number: absolute value, not in a section
+pub const IMAGE_SYM_ABSOLUTE: i16 = -1;
+/// Symbol section number: debugging entry
+pub const IMAGE_SYM_DEBUG: i16 = -2;
+/// Symbol type (upper byte): function
+pub const IMAGE_SYM_DTYPE_FUNCTION: u16 = 2;
+/// Storage class: external symbol
+pub const IMAGE_SYM_CLASS_EXTERNAL: u8 = 2;
+/// Storage class: static symbol or section
+pub const IMAGE_SYM_CLASS_STATIC: u8 = 3;
+/// Storage class: source file name, held in the auxiliary records
+pub const IMAGE_SYM_CLASS_FILE: u8 = 103;
+/// Storage class: weak external
+pub const IMAGE_SYM_CLASS_WEAK_EXTERNAL: u8 = 105;
+
 pub const DOS_HDR_MAG: u16 = 0x5A4D;
 pub const PE_HDR_MAG: u32 = 0x00004550;
 
@@ -153,6 +192,69 @@ pub struct OptionalHeader {
     pub heap_commit_size: u64,
     pub loader_flags: u32,
     pub num_rva: u32,
+    /// The data directories present, indexed by `IMAGE_DIRECTORY_ENTRY_*`
+    pub data_dirs: Vec<DataDirectory>,
+}
+
+/// Location of one of the tables the loader uses (imports, exports, ...)
+#[derive(Clone, Copy, Debug)]
+pub struct DataDirectory {
+    pub rva: u32,
+    pub size: u32,
+}
+
+pub const IMAGE_DIRECTORY_ENTRY_EXPORT: usize = 0;
+pub const IMAGE_DIRECTORY_ENTRY_IMPORT: usize = 1;
+pub const IMAGE_DIRECTORY_ENTRY_RESOURCE: usize = 2;
+pub const IMAGE_DIRECTORY_ENTRY_EXCEPTION: usize = 3;
+pub const IMAGE_DIRECTORY_ENTRY_SECURITY: usize = 4;
+pub const IMAGE_DIRECTORY_ENTRY_BASERELOC: usize = 5;
+pub const IMAGE_DIRECTORY_ENTRY_DEBUG: usize = 6;
+pub const IMAGE_DIRECTORY_ENTRY_TLS: usize = 9;
+pub const IMAGE_DIRECTORY_ENTRY_IAT: usize = 12;
+pub const IMAGE_DIRECTORY_ENTRY_DELAY_IMPORT: usize = 13;
+/// Most data directories an optional header can hold
+pub const IMAGE_NUMBEROF_DIRECTORY_ENTRIES: usize = 16;
+
+/// Bit set in a PE32 import thunk that imports by ordinal
+pub const IMAGE_ORDINAL_FLAG32: u64 = 0x80000000;
+/// Bit set in a PE32+ import thunk that imports by ordinal
+pub const IMAGE_ORDINAL_FLAG64: u64 = 0x8000000000000000;
+
+/// A function imported from a DLL
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImportedFunction {
+    /// Imported by name, with the loader's hint into the DLL's export
+    /// name table
+    Named { hint: u16, name: String },
+    /// Imported by ordinal only
+    Ordinal(u16),
+}
+
+/// A DLL named in the import directory and what is imported from it
+#[derive(Clone, Debug)]
+pub struct ImportModule {
+    pub name: String,
+    pub functions: Vec<ImportedFunction>,
+}
+
+/// Where an export resolves to
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExportTarget {
+    /// Relative virtual address of the code or data in this image
+    Rva(u32),
+    /// Forwarded to another DLL's export, e.g. `NTDLL.RtlAllocateHeap`
+    Forward(String),
+}
+
+/// An entry of the export address table
+#[derive(Clone, Debug)]
+pub struct ExportedSymbol {
+    /// `None` for exports only reachable by ordinal
+    pub name: Option<String>,
+    /// Biased ordinal, as used by importers
+    pub ordinal: u32,
+    pub target: ExportTarget,
 }
 
 pub struct SectionHeader {
@@ -168,6 +270,8 @@ pub struct SectionHeader {
     pub characteristics: u32,
 }
 
+/// COFF symbol table entry (auxiliary records are folded into the symbol
+/// they follow)
 pub struct Symbol {
     pub name: ::std::ffi::CString,
     pub value: u32,
@@ -0,0 +1,39 @@
+use serde::{Serialize, Serializer};
+use serde::ser::SerializeStruct;
+use elf::types;
+
+/// Serialize a constant newtype as its numeric value plus its symbolic name
+macro_rules! serialize_named {
+    ($($t:ty),*) => {
+        $(
+            impl Serialize for $t {
+                fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+                    let mut st = try!(s.serialize_struct(stringify!($t), 2));
+                    try!(st.serialize_field("value", &self.0));
+                    try!(st.serialize_field("name", &self.to_string()));
+                    st.end()
+                }
+            }
+        )*
+    };
+}
+
+serialize_named!(types::Class, types::Data, types::Version, types::OsAbi, types::Machine, types::Type,
+                 types::SectionType, types::SectionFlag, types::ProgType, types::ProgFlag,
+                 types::SymbolBind, types::SymbolType, types::SymbolVis, types::DynTag);
+
+#[cfg(feature = "serde-data")]
+pub fn base64<S: Serializer>(data: &[u8], s: S) -> Result<S::Ok, S::Error> {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        out.push(ALPHABET[(n >> 18) as usize & 0x3f] as char);
+        out.push(ALPHABET[(n >> 12) as usize & 0x3f] as char);
+        out.push(if chunk.len() > 1 { ALPHABET[(n >> 6) as usize & 0x3f] as char } else { '=' });
+        out.push(if chunk.len() > 2 { ALPHABET[n as usize & 0x3f] as char } else { '=' });
+    }
+    s.serialize_str(&out)
+}
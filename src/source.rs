@@ -0,0 +1,52 @@
+#[cfg(feature = "std")]
+use std::io;
+use Error;
+
+/// Minimal random-access input the header parsers read from
+///
+/// Implemented for byte slices, so headers can be parsed without `std`, and
+/// (with the `std` feature) for any `io::Read + io::Seek` through `IoSource`.
+pub trait ByteSource {
+    /// Fill `buf` with the bytes at `offset`
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> Result<(), Error>;
+    /// Total length of the input
+    fn len(&mut self) -> Result<u64, Error>;
+    /// Whether the input has no bytes at all
+    fn is_empty(&mut self) -> Result<bool, Error> {
+        self.len().map(|len| len == 0)
+    }
+}
+
+impl<'a> ByteSource for &'a [u8] {
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> Result<(), Error> {
+        match offset.checked_add(buf.len() as u64) {
+            Some(end) if end <= <[u8]>::len(self) as u64 => {
+                buf.copy_from_slice(&self[offset as usize..end as usize]);
+                Ok(())
+            },
+            _ => Err(Error::Truncated { offset: offset }),
+        }
+    }
+    fn len(&mut self) -> Result<u64, Error> {
+        Ok(<[u8]>::len(self) as u64)
+    }
+}
+
+/// Adapts an `io::Read + io::Seek` reader to `ByteSource`
+#[cfg(feature = "std")]
+pub struct IoSource<'a, R: 'a>(pub &'a mut R);
+
+#[cfg(feature = "std")]
+impl<'a, R: io::Read + io::Seek> ByteSource for IoSource<'a, R> {
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> Result<(), Error> {
+        try!(self.0.seek(io::SeekFrom::Start(offset)));
+        match self.0.read_exact(buf) {
+            Ok(()) => Ok(()),
+            Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => Err(Error::Truncated { offset: offset }),
+            Err(e) => Err(Error::Io(e)),
+        }
+    }
+    fn len(&mut self) -> Result<u64, Error> {
+        Ok(try!(self.0.seek(io::SeekFrom::End(0))))
+    }
+}
@@ -0,0 +1,212 @@
+use std::io;
+use std::str;
+use wasm::types;
+use elf;
+use {Error, Object, Section};
+
+pub struct File {
+    pub version: u32,
+    /// Sections in module order; custom sections are named by their own
+    /// name field, the others by the standard name of their id
+    pub sections: Vec<Section>,
+    pub exports: Vec<types::Export>,
+    /// Function index from the start section
+    pub start: Option<u32>,
+}
+
+/// Cursor over the module bytes, reading the LEB128 and vector encodings of
+/// the binary format
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn byte(&mut self) -> Result<u8, Error> {
+        match self.data.get(self.pos) {
+            Some(&b) => {
+                self.pos += 1;
+                Ok(b)
+            },
+            None => Err(Error::Truncated { offset: self.pos as u64 }),
+        }
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        let mut result = 0u32;
+        let mut shift = 0;
+        loop {
+            let b = try!(self.byte());
+            if shift == 28 && b & 0x70 != 0 {
+                return Err(Error::from("LEB128 value does not fit in 32 bits"));
+            }
+            result |= ((b & 0x7f) as u32) << shift;
+            if b & 0x80 == 0 {
+                return Ok(result);
+            }
+            shift += 7;
+        }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        match self.pos.checked_add(len) {
+            Some(end) if end <= self.data.len() => {
+                let out = &self.data[self.pos..end];
+                self.pos = end;
+                Ok(out)
+            },
+            _ => Err(Error::Truncated { offset: self.pos as u64 }),
+        }
+    }
+
+    fn name(&mut self) -> Result<String, Error> {
+        let len = try!(self.u32()) as usize;
+        let offset = self.pos as u64;
+        let raw = try!(self.bytes(len));
+        match str::from_utf8(raw) {
+            Ok(s) => Ok(String::from(s)),
+            Err(_) => Err(Error::Other(format!("invalid UTF-8 name at offset {:#x}", offset))),
+        }
+    }
+}
+
+impl File {
+    pub fn parse<R: io::Read + io::Seek>(r: &mut R) -> Result<File, Error> {
+        try!(r.seek(io::SeekFrom::Start(0)));
+        let mut data = Vec::new();
+        try!(r.read_to_end(&mut data));
+        File::parse_bytes(&data)
+    }
+
+    /// Parse a module already held in memory
+    pub fn parse_bytes(data: &[u8]) -> Result<File, Error> {
+        if data.len() < 8 {
+            return Err(Error::Truncated { offset: 0 });
+        }
+        if data[0..4] != types::WASM_MAGIC {
+            return Err(Error::BadMagic);
+        }
+        let version = data[4] as u32 | (data[5] as u32) << 8 | (data[6] as u32) << 16 | (data[7] as u32) << 24;
+        if version != types::WASM_VERSION {
+            return Err(Error::Other(format!("unsupported wasm version {}", version)));
+        }
+
+        let mut r = Reader { data: data, pos: 8 };
+        let mut sections = Vec::new();
+        let mut exports = Vec::new();
+        let mut start = None;
+        while r.pos < data.len() {
+            let id = types::SectionId(try!(r.byte()));
+            let size = try!(r.u32()) as usize;
+            let offset = r.pos;
+            let payload = try!(r.bytes(size));
+            let mut p = Reader { data: payload, pos: 0 };
+
+            let name = if id == types::SEC_CUSTOM {
+                try!(p.name())
+            } else {
+                format!("{}", id)
+            };
+            match id {
+                types::SEC_EXPORT => {
+                    let count = try!(p.u32());
+                    for _ in 0..count {
+                        let name = try!(p.name());
+                        let kind = types::ExternalKind(try!(p.byte()));
+                        let index = try!(p.u32());
+                        exports.push(types::Export { name: name, kind: kind, index: index });
+                    }
+                },
+                types::SEC_START => start = Some(try!(p.u32())),
+                _ => {},
+            }
+
+            // Custom section contents start after the name
+            let skip = if id == types::SEC_CUSTOM { p.pos } else { 0 };
+            let flags = match id {
+                types::SEC_CODE => elf::types::SHF_ALLOC.0 | elf::types::SHF_EXECINSTR.0,
+                types::SEC_DATA => elf::types::SHF_ALLOC.0 | elf::types::SHF_WRITE.0,
+                _ => 0,
+            };
+            sections.push(Section {
+                name: name,
+                index: sections.len(),
+                addr: 0,
+                offset: (offset + skip) as u64,
+                size: (size - skip) as u64,
+                flags: elf::types::SectionFlag(flags),
+                shtype: elf::types::SHT_PROGBITS,
+                link: 0,
+                info: 0,
+                entsize: 0,
+                addralign: 0,
+                nobits: false,
+                compressed_size: None,
+                loaded: true,
+                data: payload[skip..].to_vec(),
+            });
+        }
+
+        Ok(File {
+            version: version,
+            sections: sections,
+            exports: exports,
+            start: start,
+        })
+    }
+
+    /// Names of the exports of the given kind, in export section order
+    pub fn export_names(&self, kind: types::ExternalKind) -> Vec<&str> {
+        self.exports.iter().filter(|e| e.kind == kind).map(|e| &e.name[..]).collect()
+    }
+}
+
+impl Object for File {
+    fn format(&self) -> ::Format {
+        ::Format::Wasm
+    }
+    /// One symbol per export, its address being the index in the export's
+    /// index space
+    fn symbols(&self) -> Vec<::Symbol> {
+        self.exports.iter().map(|e| ::Symbol {
+            name: e.name.clone(),
+            address: e.index as u64,
+            size: 0,
+            kind: match e.kind {
+                types::EXT_FUNC => ::SymbolKind::Function,
+                types::EXT_MEMORY | types::EXT_GLOBAL | types::EXT_TABLE => ::SymbolKind::Data,
+                _ => ::SymbolKind::Unknown,
+            },
+            global: true,
+            defined: true,
+        }).collect()
+    }
+    fn arch(&self) -> ::Arch {
+        ::Arch::Wasm
+    }
+    fn get_section(&self, name: &str) -> Option<&Section> {
+        self.sections.iter().find(|s| s.name == name)
+    }
+    /// Index of the start function, or 0 if there is none
+    fn entry(&self) -> u64 {
+        self.start.unwrap_or(0) as u64
+    }
+    fn obj_type(&self) -> ::ObjectType {
+        // Object files from the LLVM toolchain carry linking metadata, and
+        // dynamic libraries (e.g. emscripten side modules) a dylink section
+        if self.get_section("linking").is_some() {
+            ::ObjectType::Relocatable
+        } else if self.get_section("dylink.0").is_some() || self.get_section("dylink").is_some() {
+            ::ObjectType::SharedObject
+        } else {
+            ::ObjectType::Executable
+        }
+    }
+    fn endianness(&self) -> ::Endianness {
+        ::Endianness::Little
+    }
+    /// Linear memory is addressed with 32 bits (before the memory64 proposal)
+    fn width(&self) -> ::Width {
+        ::Width::W32
+    }
+}
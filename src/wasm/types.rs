@@ -0,0 +1,109 @@
+use std::fmt;
+
+/// WebAssembly binary magic, `\0asm`
+pub const WASM_MAGIC: [u8; 4] = [0x00, 0x61, 0x73, 0x6d];
+/// The only binary format version in use
+pub const WASM_VERSION: u32 = 1;
+
+/// Module section identifier
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SectionId(pub u8);
+/// Custom section, named by its first field
+pub const SEC_CUSTOM: SectionId = SectionId(0);
+/// Function signatures
+pub const SEC_TYPE: SectionId = SectionId(1);
+/// Imported functions, tables, memories and globals
+pub const SEC_IMPORT: SectionId = SectionId(2);
+/// Signature index of each function defined in the module
+pub const SEC_FUNCTION: SectionId = SectionId(3);
+/// Table definitions
+pub const SEC_TABLE: SectionId = SectionId(4);
+/// Linear memory definitions
+pub const SEC_MEMORY: SectionId = SectionId(5);
+/// Global variable definitions
+pub const SEC_GLOBAL: SectionId = SectionId(6);
+/// Exported functions, tables, memories and globals
+pub const SEC_EXPORT: SectionId = SectionId(7);
+/// Start function index
+pub const SEC_START: SectionId = SectionId(8);
+/// Table initializers
+pub const SEC_ELEMENT: SectionId = SectionId(9);
+/// Function bodies
+pub const SEC_CODE: SectionId = SectionId(10);
+/// Linear memory initializers
+pub const SEC_DATA: SectionId = SectionId(11);
+/// Number of data segments, for single-pass validation
+pub const SEC_DATACOUNT: SectionId = SectionId(12);
+
+impl fmt::Debug for SectionId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:#x}", self.0)
+    }
+}
+
+impl fmt::Display for SectionId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let str = match *self {
+            SEC_CUSTOM => "custom",
+            SEC_TYPE => "type",
+            SEC_IMPORT => "import",
+            SEC_FUNCTION => "function",
+            SEC_TABLE => "table",
+            SEC_MEMORY => "memory",
+            SEC_GLOBAL => "global",
+            SEC_EXPORT => "export",
+            SEC_START => "start",
+            SEC_ELEMENT => "element",
+            SEC_CODE => "code",
+            SEC_DATA => "data",
+            SEC_DATACOUNT => "datacount",
+            _ => "unknown",
+        };
+        write!(f, "{}", str)
+    }
+}
+
+/// Kind of entity an import or export refers to
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct ExternalKind(pub u8);
+pub const EXT_FUNC: ExternalKind = ExternalKind(0);
+pub const EXT_TABLE: ExternalKind = ExternalKind(1);
+pub const EXT_MEMORY: ExternalKind = ExternalKind(2);
+pub const EXT_GLOBAL: ExternalKind = ExternalKind(3);
+pub const EXT_TAG: ExternalKind = ExternalKind(4);
+
+impl fmt::Debug for ExternalKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:#x}", self.0)
+    }
+}
+
+impl fmt::Display for ExternalKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let str = match *self {
+            EXT_FUNC => "func",
+            EXT_TABLE => "table",
+            EXT_MEMORY => "memory",
+            EXT_GLOBAL => "global",
+            EXT_TAG => "tag",
+            _ => "unknown",
+        };
+        write!(f, "{}", str)
+    }
+}
+
+/// An entry of the export section
+#[derive(Clone, Debug)]
+pub struct Export {
+    pub name: String,
+    pub kind: ExternalKind,
+    /// Index into the function, table, memory or global index space
+    /// (which counts imports before definitions)
+    pub index: u32,
+}
+
+impl fmt::Display for Export {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Export: {} {} {}", self.kind, self.index, self.name)
+    }
+}